@@ -0,0 +1,44 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use kf_vs_ak::{combine_records, simulate, Record, SimulationParams, TaxParams};
+use std::collections::BTreeMap;
+use std::hint::black_box;
+
+const RANGE: (i32, i32) = (1993, 2023);
+const LENGTHS: [i32; 5] = [5, 10, 15, 20, 25];
+
+/// Thirty years of an index alternating between good and bad years, with
+/// an SLR that drifts down like the real one.
+fn records() -> BTreeMap<i32, Record> {
+    let mut index = BTreeMap::new();
+    let mut slr = BTreeMap::new();
+    let mut value = 100.0;
+    for year in RANGE.0..=RANGE.1 {
+        let i = year - RANGE.0;
+        value *= if i % 3 == 2 { 0.85 } else { 1.15 };
+        index.insert(year, value);
+        slr.insert(year, 6.0 - i as f64 * 0.18);
+    }
+    combine_records(&index, &slr, &TaxParams::default())
+}
+
+fn bench_simulate(c: &mut Criterion) {
+    let records = records();
+    let params = SimulationParams::default();
+    let single_thread = rayon::ThreadPoolBuilder::new()
+        .num_threads(1)
+        .build()
+        .unwrap();
+
+    c.bench_function("simulate, one thread", |b| {
+        b.iter(|| {
+            single_thread
+                .install(|| simulate(black_box(&records), &LENGTHS, RANGE, &params).unwrap())
+        })
+    });
+    c.bench_function("simulate, rayon pool", |b| {
+        b.iter(|| simulate(black_box(&records), &LENGTHS, RANGE, &params).unwrap())
+    });
+}
+
+criterion_group!(benches, bench_simulate);
+criterion_main!(benches);
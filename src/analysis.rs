@@ -0,0 +1,107 @@
+use crate::simulate::SeriesEntry;
+use crate::stats::mean;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Average AK and KF outcome for one holding length.
+#[derive(Debug, Serialize)]
+pub struct LengthAverages {
+    pub length: i32,
+    pub aktiekonto: f64,
+    #[serde(rename = "kapitalforsakring")]
+    pub kapitalförsäkring: f64,
+}
+
+/// Averages per holding length, in ascending length order, skipping lengths
+/// without any start year.
+pub fn length_averages(series: &BTreeMap<i32, Vec<SeriesEntry>>) -> Vec<LengthAverages> {
+    series
+        .iter()
+        .filter_map(|(&length, entries)| {
+            let aktiekonto: Vec<f64> = entries.iter().map(|e| e.aktiekonto).collect();
+            let kapitalförsäkring: Vec<f64> = entries.iter().map(|e| e.kapitalförsäkring).collect();
+            Some(LengthAverages {
+                length,
+                aktiekonto: mean(&aktiekonto)?,
+                kapitalförsäkring: mean(&kapitalförsäkring)?,
+            })
+        })
+        .collect()
+}
+
+/// The shortest holding length where KF's average outcome beats AK's.
+pub fn find_breakeven(averages: &[LengthAverages]) -> Option<i32> {
+    averages
+        .iter()
+        .find(|a| a.kapitalförsäkring > a.aktiekonto)
+        .map(|a| a.length)
+}
+
+/// Where the years of a holding period lie relative to a change of the tax
+/// rules that takes effect in a given year.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegimeWindow {
+    /// Every year is before the change.
+    Before,
+    /// The period has years on both sides of the change.
+    Straddling,
+    /// Every year is from the change on.
+    After,
+}
+
+impl RegimeWindow {
+    /// The window of the holding period of `length` years from `start_year`,
+    /// which covers `start_year + 1` to `start_year + length`.
+    pub fn of(start_year: i32, length: i32, change_year: i32) -> Self {
+        if start_year + length < change_year {
+            RegimeWindow::Before
+        } else if start_year + 1 >= change_year {
+            RegimeWindow::After
+        } else {
+            RegimeWindow::Straddling
+        }
+    }
+}
+
+/// The start years of one holding length in one [`RegimeWindow`].
+#[derive(Debug)]
+pub struct RegimeGroup {
+    pub length: i32,
+    pub window: RegimeWindow,
+    pub count: usize,
+    /// Average of [`SeriesEntry::kf_difference_pct`], `None` without start
+    /// years.
+    pub mean_kf_difference: Option<f64>,
+}
+
+/// Splits the start years of every length by where their holding period
+/// lies relative to `change_year`, in ascending length order and with all
+/// three windows of each length.
+pub fn regime_split(
+    series: &BTreeMap<i32, Vec<SeriesEntry>>,
+    change_year: i32,
+) -> Vec<RegimeGroup> {
+    let windows = [
+        RegimeWindow::Before,
+        RegimeWindow::Straddling,
+        RegimeWindow::After,
+    ];
+    series
+        .iter()
+        .flat_map(|(&length, entries)| {
+            windows.into_iter().map(move |window| {
+                let differences: Vec<f64> = entries
+                    .iter()
+                    .filter(|e| RegimeWindow::of(e.start_year, length, change_year) == window)
+                    .map(SeriesEntry::kf_difference_pct)
+                    .collect();
+                RegimeGroup {
+                    length,
+                    window,
+                    count: differences.len(),
+                    mean_kf_difference: mean(&differences),
+                }
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,82 @@
+use crate::parse::RecordOmxs30;
+use chrono::DateTime;
+use serde::Deserialize;
+use std::time::Duration;
+
+const CHART_URL: &str = "https://query1.finance.yahoo.com/v8/finance/chart/%5EOMX";
+
+#[derive(Deserialize)]
+struct ChartResponse {
+    chart: Chart,
+}
+
+#[derive(Deserialize)]
+struct Chart {
+    result: Option<Vec<ChartResult>>,
+    error: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct ChartResult {
+    timestamp: Vec<i64>,
+    indicators: Indicators,
+}
+
+#[derive(Deserialize)]
+struct Indicators {
+    quote: Vec<Quote>,
+}
+
+#[derive(Deserialize)]
+struct Quote {
+    close: Vec<Option<f64>>,
+}
+
+/// Parses a Yahoo Finance chart response into daily closing values. Days
+/// without a close (holidays, the current unfinished day) are skipped.
+pub fn parse_chart_json(json: &str) -> Result<Vec<RecordOmxs30>, Box<dyn std::error::Error>> {
+    let response: ChartResponse = serde_json::from_str(json)?;
+    if let Some(error) = response.chart.error {
+        return Err(format!("chart API error: {error}").into());
+    }
+    let result = response
+        .chart
+        .result
+        .and_then(|results| results.into_iter().next())
+        .ok_or("chart API returned no result")?;
+    let closes = &result
+        .indicators
+        .quote
+        .first()
+        .ok_or("chart API returned no quotes")?
+        .close;
+
+    let mut records = Vec::new();
+    for (&timestamp, close) in result.timestamp.iter().zip(closes) {
+        let Some(value) = *close else { continue };
+        let date = DateTime::from_timestamp(timestamp, 0)
+            .ok_or("chart API returned an invalid timestamp")?
+            .date_naive();
+        records.push(RecordOmxs30 { date, value });
+    }
+    if records.is_empty() {
+        return Err("chart API returned no closing values".into());
+    }
+    Ok(records)
+}
+
+/// Downloads the full daily OMXS30 history.
+pub fn fetch_omxs30() -> Result<Vec<RecordOmxs30>, Box<dyn std::error::Error>> {
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(Duration::from_secs(30)))
+        .build()
+        .into();
+    let now = chrono::Utc::now().timestamp();
+    let body = agent
+        .get(format!("{CHART_URL}?period1=0&period2={now}&interval=1d"))
+        .header("User-Agent", "kf_vs_ak")
+        .call()?
+        .body_mut()
+        .read_to_string()?;
+    parse_chart_json(&body)
+}
@@ -0,0 +1,58 @@
+use crate::simulate::SeriesEntry;
+use std::collections::BTreeMap;
+
+/// Converts each entry's final values from nominal kronor of its end year
+/// into kronor of `base_year`. Without a base year each entry is expressed in
+/// kronor of its own start year, which makes the values real multipliers.
+/// With one, each entry's initial capital is converted from its start year
+/// into the base year, so that its multipliers and CAGR are unchanged.
+pub fn deflate(
+    series: &mut BTreeMap<i32, Vec<SeriesEntry>>,
+    cpi_by_year: &BTreeMap<i32, f64>,
+    base_year: Option<i32>,
+) -> Result<(), String> {
+    let mut needed_years: Vec<i32> = series
+        .iter()
+        .flat_map(|(&length, entries)| {
+            entries
+                .iter()
+                .flat_map(move |e| [e.start_year, e.start_year + length])
+        })
+        .chain(base_year)
+        .collect();
+    needed_years.sort_unstable();
+    needed_years.dedup();
+
+    let missing: Vec<String> = needed_years
+        .iter()
+        .filter(|year| cpi_by_year.get(year).is_none_or(|&cpi| cpi <= 0.0))
+        .map(|year| year.to_string())
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!("no CPI value for years: {}", missing.join(", ")));
+    }
+
+    for (&length, entries) in series.iter_mut() {
+        for e in entries {
+            let base = cpi_by_year[&base_year.unwrap_or(e.start_year)];
+            let factor = base / cpi_by_year[&(e.start_year + length)];
+            e.aktiekonto *= factor;
+            e.kapitalförsäkring *= factor;
+            e.kapitalförsäkring_tax *= factor;
+            e.isk *= factor;
+            if let Some(drag_only) = &mut e.drag_only {
+                *drag_only *= factor;
+            }
+            if let Some(benchmark) = &mut e.benchmark {
+                *benchmark *= factor;
+            }
+            // The amount invested moves to the base year's kronor too, so
+            // that the CAGR stays the real one whatever the base year.
+            if base_year.is_some() {
+                let capital = e.initial_capital.unwrap_or(1.0);
+                e.initial_capital = Some(capital * base / cpi_by_year[&e.start_year]);
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,70 @@
+mod analysis;
+#[cfg(feature = "cli")]
+mod fetch;
+mod inflation;
+mod monte_carlo;
+mod parse;
+#[cfg(feature = "cli")]
+mod plot;
+mod report;
+mod results;
+#[cfg(feature = "cli")]
+mod sheet;
+mod simulate;
+mod stats;
+mod summary;
+mod switch;
+mod tax;
+mod trajectory;
+mod validate;
+mod withdraw;
+mod yearly;
+
+pub use analysis::{
+    find_breakeven, length_averages, regime_split, LengthAverages, RegimeGroup, RegimeWindow,
+};
+#[cfg(feature = "cli")]
+pub use fetch::{fetch_omxs30, parse_chart_json};
+pub use inflation::deflate;
+pub use monte_carlo::{monte_carlo, MonteCarloSummary};
+pub use parse::{
+    parse_cpi_line, parse_number, parse_omxs30_line, parse_slr_line, parse_swedish_number,
+    InvalidNumber, NumberLocale, ParseError, RecordCpi, RecordOmxs30, RecordSLR, SLR_DELIMITER,
+    SLR_RATE_COLUMN,
+};
+#[cfg(feature = "cli")]
+pub use plot::plot_series;
+pub use report::{
+    sort_entries, write_breakeven_csv, write_breakeven_json, write_breakeven_markdown,
+    write_breakeven_text, write_combined_csv, write_combined_json, write_combined_markdown,
+    write_combined_text, write_csv, write_deferral, write_histogram, write_index_header,
+    write_json, write_markdown, write_monte_carlo, write_overview, write_regime_split,
+    write_series, write_switches, write_tax_comparison, write_tax_explanation, write_text,
+    write_trajectory, write_winner, write_withdrawals, Amounts, IndexAverages, IndexRecords,
+    IndexSeries, InputFile, RunMeta, SortKey, DEFAULT_PRECISION,
+};
+pub use results::{run, Results, RunConfig, BOOTSTRAP_LEVEL};
+#[cfg(feature = "cli")]
+pub use sheet::{is_spreadsheet, sheet_lines, SPREADSHEET_EXTENSIONS};
+pub use simulate::{
+    check_index_values, combine_records, neutralize_years, simulate, AkRealize, Benchmark,
+    BenchmarkTax, ContributionInterval, ContributionPlan, Record, SeriesEntry, SimulationParams,
+    YearStep,
+};
+pub use stats::{
+    bootstrap_mean, cagr, geometric_mean, histogram, irr, linear_regression, mean, median,
+    percentile, sample_std_dev, sharpe_ratio, ConfidenceInterval, LinearFit,
+};
+pub use summary::{summarize, ColumnSummary, SeriesSummary, StartYearOutcome};
+pub use switch::{mean_switch_after, optimize_switch, SwitchEntry};
+pub use tax::{
+    calculate_avkastningsskatt, explain_avkastningsskatt, parse_tax_schedule, TaxExplanation,
+    TaxParams, TaxPeriod,
+};
+pub use trajectory::{trajectory, TrajectoryYear};
+pub use validate::{find_outliers, Limits, Outlier, MAX_YEARLY_RATIO, MIN_YEARLY_RATIO};
+pub use withdraw::{simulate_withdrawals, WithdrawalEntry};
+pub use yearly::{
+    common_years, coverage_gaps, dedup_dates, fill_gaps, values_by_reference, values_by_year, Fill,
+    OnDuplicate, SlrReference, YearAnchor,
+};
@@ -1,230 +1,2250 @@
-use chrono::Datelike;
-use chrono::NaiveDate;
-use std::collections::HashMap;
-use std::collections::HashSet;
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, ValueEnum};
+use flate2::read::GzDecoder;
+use kf_vs_ak::{
+    check_index_values, combine_records, common_years, coverage_gaps, dedup_dates,
+    explain_avkastningsskatt, fetch_omxs30, fill_gaps, find_outliers, is_spreadsheet,
+    length_averages, monte_carlo, neutralize_years, optimize_switch, parse_cpi_line,
+    parse_omxs30_line, parse_slr_line, parse_tax_schedule, plot_series, regime_split, sheet_lines,
+    simulate, simulate_withdrawals, sort_entries, trajectory, values_by_reference,
+    write_breakeven_csv, write_breakeven_json, write_breakeven_markdown, write_breakeven_text,
+    write_combined_csv, write_combined_json, write_combined_markdown, write_combined_text,
+    write_csv, write_deferral, write_histogram, write_index_header, write_json, write_markdown,
+    write_monte_carlo, write_regime_split, write_switches, write_tax_comparison,
+    write_tax_explanation, write_text, write_trajectory, write_winner, write_withdrawals,
+    AkRealize, Amounts, Benchmark, BenchmarkTax, ContributionInterval, ContributionPlan, Fill,
+    IndexAverages, IndexSeries, InputFile, Limits, NumberLocale, OnDuplicate, Outlier, ParseError,
+    Results, RunConfig, RunMeta, SimulationParams, SlrReference, SortKey, TaxParams, TaxPeriod,
+    YearAnchor, DEFAULT_PRECISION, SLR_DELIMITER, SLR_RATE_COLUMN,
+};
+use rand::rngs::StdRng;
+use rand::{RngExt, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
 use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
-use std::str::FromStr;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
 
-struct RecordOmxs30 {
-    date: NaiveDate,
-    value: f32,
+/// Environment variables that replace the default and `--config` input
+/// paths; the flags still take precedence.
+const OMXS30_ENV: &str = "KF_VS_AK_OMXS30";
+const SLR_ENV: &str = "KF_VS_AK_SLR";
+
+#[derive(Parser)]
+#[command(
+    about = "Compare kapitalförsäkring and aktiekonto returns on OMXS30",
+    after_help = "Set RUST_LOG=kf_vs_ak=debug to log the return, tax and account values of every simulated year."
+)]
+struct Args {
+    /// OMXS30 daily values, tab separated; `-` reads stdin [env: KF_VS_AK_OMXS30]
+    #[arg(long, value_name = "PATH", default_value = "omxs30.txt")]
+    omxs30: PathBuf,
+
+    /// Statslåneränta, semicolon separated; `-` reads stdin [env: KF_VS_AK_SLR]
+    #[arg(long, value_name = "PATH", default_value = "stadslåneränta.csv")]
+    slr: PathBuf,
+
+    /// Use this statslåneränta in percent for every year instead of reading
+    /// --slr, for the tax and as the risk-free rate
+    #[arg(long, value_name = "PERCENT", conflicts_with = "slr")]
+    slr_override: Option<f64>,
+
+    /// OMXS30 gross (total-return) daily values, in the format of --omxs30,
+    /// read instead of it with `--index-type gross`
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["index", "fetch"])]
+    gross: Option<PathBuf>,
+
+    /// Whether to simulate on the price index or on the gross index
+    #[arg(long, value_name = "TYPE", value_enum, default_value_t = IndexType::Price)]
+    index_type: IndexType,
+
+    /// More OMXS30 daily values, merged with those of --omxs30 (or --gross)
+    /// before picking each year's value; repeat for more files. Dates in
+    /// several files are handled by --on-duplicate
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["index", "fetch"])]
+    append_data: Vec<PathBuf>,
+
+    /// Index daily values to compare, tab separated like --omxs30; repeat
+    /// to run the simulation for each index
+    #[arg(long, value_name = "NAME=PATH", value_parser = parse_index, conflicts_with_all = ["omxs30", "fetch"])]
+    index: Vec<(String, PathBuf)>,
+
+    /// Fail unless the index has this value on this date, e.g.
+    /// `2021-12-30=2345.67`; repeat for more dates. Every index is checked
+    #[arg(long, value_name = "DATE=LEVEL", value_parser = parse_assert_value)]
+    assert_value: Vec<(NaiveDate, f64)>,
+
+    /// Largest difference from the --assert-value level that still passes
+    #[arg(long, value_name = "VALUE", default_value_t = 0.005)]
+    assert_tolerance: f64,
+
+    /// Download OMXS30 closing values instead of reading --omxs30
+    #[arg(long)]
+    fetch: bool,
+
+    /// Run on a small bundled dataset instead of --omxs30 and --slr; also
+    /// used when neither is given and the default files are missing
+    #[arg(long, conflicts_with_all = ["omxs30", "slr", "append_data", "index", "fetch"])]
+    demo: bool,
+
+    /// Where downloaded values are cached between runs
+    #[arg(long, value_name = "PATH", default_value = "omxs30_fetched.txt")]
+    fetch_cache: PathBuf,
+
+    /// Use the first or last observation of each year; `first` shifts every
+    /// yearly return window one observation later
+    #[arg(long, value_enum, default_value_t = YearAnchor::Last)]
+    year_anchor: YearAnchor,
+
+    /// SLR column to read (1-based): 2 is "Räntesats %", the rate on each
+    /// date, 3 is "Medelvärde hittills i år", the year-to-date average
+    #[arg(long, value_name = "N", default_value_t = SLR_RATE_COLUMN, value_parser = column_parser(2))]
+    slr_column: usize,
+
+    /// Which SLR observations set each year's avkastningsskatt: `nov30` is
+    /// Skatteverket's rule, the rate of November 30 the year before
+    #[arg(long, value_name = "REFERENCE", value_enum, default_value_t = SlrReference::YearEnd)]
+    slr_reference: SlrReference,
+
+    /// Field delimiter of the SLR file
+    #[arg(long, value_name = "CHAR", default_value_t = SLR_DELIMITER)]
+    slr_delimiter: char,
+
+    /// How the numbers of the text inputs are written; spreadsheet cells
+    /// and the --fetch cache always have a decimal point
+    #[arg(long, value_name = "LOCALE", value_enum, default_value_t = NumberLocale::Sv)]
+    input_locale: NumberLocale,
+
+    /// How input files are compressed; `auto` goes by the .gz extension
+    #[arg(long, value_enum, default_value_t = Compression::Auto)]
+    compression: Compression,
+
+    /// Decompress input files with gzip even without a .gz extension, the
+    /// same as --compression gzip
+    #[arg(long, conflicts_with = "compression")]
+    gzip: bool,
+
+    /// Sheet to read from .xlsx, .xls and .ods inputs [default: the first]
+    #[arg(long, value_name = "NAME")]
+    sheet: Option<String>,
+
+    /// Column of the dates in spreadsheet inputs, counting from 1
+    #[arg(long, value_name = "N", default_value_t = 1, value_parser = column_parser(1))]
+    date_column: usize,
+
+    /// Column of the values in spreadsheet inputs, counting from 1
+    #[arg(long, value_name = "N", default_value_t = 2, value_parser = column_parser(1))]
+    value_column: usize,
+
+    /// Keep the parsed inputs in this file and read them from it while no
+    /// input file is newer
+    #[arg(long, value_name = "PATH")]
+    cache: Option<PathBuf>,
+
+    /// Which row to keep when a date appears more than once, or `error` to
+    /// reject such input
+    #[arg(long, value_enum, default_value_t = OnDuplicate::Last)]
+    on_duplicate: OnDuplicate,
+
+    /// How to treat years missing in the middle of an input series
+    #[arg(long, value_enum, default_value_t = Fill::None)]
+    fill: Fill,
+
+    /// Fail instead of warning when a year of the simulation is missing
+    /// from the index or the SLR
+    #[arg(long)]
+    strict: bool,
+
+    /// First start year of the simulation [default: the first year with
+    /// data in every input]
+    #[arg(long)]
+    from_year: Option<i32>,
+
+    /// Last year of the simulation [default: the last year with data in
+    /// every input]
+    #[arg(long)]
+    to_year: Option<i32>,
+
+    /// Warn about index values below this
+    #[arg(long, value_name = "VALUE")]
+    min_value: Option<f64>,
+
+    /// Warn about index values above this
+    #[arg(long, value_name = "VALUE")]
+    max_value: Option<f64>,
+
+    /// Years to leave out, comma separated, as set by --exclude-mode
+    #[arg(long, value_name = "YEARS", value_delimiter = ',')]
+    exclude_years: Vec<i32>,
+
+    /// Whether --exclude-years drops those start years or makes those years
+    /// pass without a return or tax in every holding period
+    #[arg(long, value_enum, default_value_t = ExcludeMode::Start, requires = "exclude_years")]
+    exclude_mode: ExcludeMode,
+
+    /// Holding lengths in years, comma separated
+    #[arg(long, value_delimiter = ',', default_values_t = [5, 10, 15, 20, 25])]
+    lengths: Vec<i32>,
+
+    /// Run and print only this holding length, like --lengths N
+    #[arg(long, value_name = "N", conflicts_with_all = ["lengths", "find_breakeven"])]
+    only_length: Option<i32>,
+
+    /// Annual kapitalförsäkring fee in percent
+    #[arg(long, value_name = "PERCENT", default_value_t = 0.0)]
+    kf_fee: f64,
+
+    /// Invest this amount every interval instead of a single unit up front
+    #[arg(long, value_name = "AMOUNT")]
+    contribution: Option<f64>,
+
+    /// How often the contribution is made
+    #[arg(long, value_enum, default_value_t = ContributionInterval::Yearly)]
+    contribution_interval: ContributionInterval,
+
+    /// Month of the first simulated year the single unit is invested in;
+    /// that year's return and tax are prorated linearly
+    #[arg(long, value_name = "MONTH", default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..=12), conflicts_with = "contribution")]
+    entry_month: u32,
+
+    /// Invest this many kronor up front instead of a single unit, so final
+    /// values are amounts rather than multipliers
+    #[arg(long, value_name = "AMOUNT", conflicts_with = "contribution")]
+    initial_capital: Option<f64>,
+
+    /// Label such as SEK after each final value, with --initial-capital or
+    /// --contribution
+    #[arg(long, value_name = "LABEL")]
+    currency_label: Option<String>,
+
+    /// Group the thousands of final values with spaces, as in 1 234 567,
+    /// with --initial-capital or --contribution
+    #[arg(long)]
+    group_thousands: bool,
+
+    /// Order of the start years in each holding length's output
+    #[arg(long, value_enum, default_value_t = SortKey::Year)]
+    sort: SortKey,
+
+    /// Sort in descending order
+    #[arg(long)]
+    desc: bool,
+
+    /// Consumer price index, semicolon separated like the SLR file
+    #[arg(long, value_name = "PATH")]
+    cpi: Option<PathBuf>,
+
+    /// Deflate final values with the CPI to constant kronor
+    #[arg(long, requires = "cpi")]
+    real: bool,
+
+    /// Year whose kronor --real values are expressed in [default: each start year]
+    #[arg(long, requires = "real")]
+    base_year: Option<i32>,
+
+    /// Share of the schablonintäkt paid as avkastningsskatt
+    #[arg(long, value_name = "RATE", default_value_t = TaxParams::default().tax_base_rate)]
+    tax_base_rate: f64,
+
+    /// Lowest schablonintäkt in percent, from 2012
+    #[arg(long, value_name = "PERCENT", default_value_t = TaxParams::default().minimum_tax_percentage)]
+    min_tax_pct: f64,
+
+    /// Avkastningsskatt parameters by year, one
+    /// `from_year,to_year,supplement,floor,base_rate` line per period;
+    /// other years use the built-in rule
+    #[arg(long, value_name = "PATH")]
+    tax_schedule: Option<PathBuf>,
+
+    /// Tax on aktiekonto gains when sold
+    #[arg(long, value_name = "RATE", default_value_t = SimulationParams::default().capital_gains_rate)]
+    capital_gains_rate: f64,
+
+    /// Annual dividend yield in percent added to the price index return, a
+    /// flat approximation of a total-return index; taxed yearly on the AK
+    #[arg(long, value_name = "PERCENT", default_value_t = 0.0)]
+    dividend_yield: f64,
+
+    /// Also simulate a savings account with this fixed annual interest in
+    /// percent, printed as the last column
+    #[arg(long, value_name = "PERCENT")]
+    benchmark_rate: Option<f64>,
+
+    /// Also simulate an untaxed index account that only pays this annual fee
+    /// in percent, printed after the ISK
+    #[arg(long, value_name = "PERCENT")]
+    drag_only_fee: Option<f64>,
+
+    /// Compute the Sharpe ratios from log returns, ln(end / start), instead
+    /// of simple returns. The volatility is always of log returns, and the
+    /// final values are not affected
+    #[arg(long)]
+    log_returns: bool,
+
+    /// Whether the --benchmark-rate account pays schablon tax like the KF
+    #[arg(long, value_enum, default_value_t = BenchmarkTax::None, requires = "benchmark_rate")]
+    benchmark_tax: BenchmarkTax,
+
+    /// Bootstrap this many synthetic paths per length instead of using history
+    #[arg(long, value_name = "N")]
+    monte_carlo: Option<usize>,
+
+    /// Add a 90% confidence interval to the average KF vs AK difference of
+    /// each length, from this many bootstrap resamples of its start years
+    #[arg(long, value_name = "N", conflicts_with_all = ["monte_carlo", "find_breakeven"])]
+    bootstrap: Option<usize>,
+
+    /// Seed for --monte-carlo and --bootstrap [default: random, printed in
+    /// the text output]
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// List the line number and content of every skipped input line
+    #[arg(long)]
+    verbose: bool,
+
+    /// Only print the results and errors, not warnings and notes
+    #[arg(long, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Whether the aktiekonto is sold (and its gain taxed) at the end
+    #[arg(long, value_enum, default_value_t = AkRealize::Sell)]
+    ak_realize: AkRealize,
+
+    /// Print each year's index value, SLR and avkastningsskatt as used by
+    /// the simulation, then exit
+    #[arg(long, conflicts_with_all = ["monte_carlo", "find_breakeven", "withdraw", "optimize_switch", "histogram", "trajectory", "compare_tax", "regime_split", "explain", "plot"])]
+    dump_combined: bool,
+
+    /// Find the shortest holding length where KF beats AK on average
+    #[arg(long)]
+    find_breakeven: bool,
+
+    /// After each holding length, withdraw this percentage of the final
+    /// value every year and report how long each account lasts
+    #[arg(long, value_name = "PERCENT", conflicts_with_all = ["monte_carlo", "find_breakeven", "plot"])]
+    withdraw: Option<f64>,
+
+    /// For each start year, find the year to move the money from AK to KF
+    /// that gives the highest final value, paying capital-gains tax once
+    #[arg(long, conflicts_with_all = ["monte_carlo", "find_breakeven", "withdraw", "plot", "contribution"])]
+    optimize_switch: bool,
+
+    /// Print a histogram of the KF minus AK percentage difference over the
+    /// start years of this holding length
+    #[arg(long, value_name = "LENGTH", conflicts_with_all = ["monte_carlo", "find_breakeven", "withdraw", "optimize_switch", "plot"])]
+    histogram: Option<i32>,
+
+    /// Width of each --histogram bucket in percentage points
+    #[arg(long, value_name = "POINTS", default_value_t = 5.0)]
+    bucket_width: f64,
+
+    /// Print the AK and KF balances at the end of every year from this
+    /// start year to --to-year, with the return and the tax of each year
+    #[arg(long, value_name = "START_YEAR", conflicts_with_all = ["monte_carlo", "find_breakeven", "withdraw", "optimize_switch", "histogram", "plot"])]
+    trajectory: Option<i32>,
+
+    /// Print the KF win rate of every holding length under each of these
+    /// tax regimes, named in the `tax_regimes` table of --config; `current`
+    /// is the tax given by the other flags
+    #[arg(long, value_name = "NAME", value_delimiter = ',', conflicts_with_all = ["monte_carlo", "find_breakeven", "withdraw", "optimize_switch", "histogram", "trajectory", "plot"])]
+    compare_tax: Vec<String>,
+
+    /// Print the average KF vs AK difference of the holding periods wholly
+    /// before this year, straddling it and wholly from it on, e.g. 2018
+    /// for the raised KF supplement
+    #[arg(long, value_name = "YEAR", conflicts_with_all = ["monte_carlo", "find_breakeven", "withdraw", "optimize_switch", "histogram", "trajectory", "compare_tax", "plot"])]
+    regime_split: Option<i32>,
+
+    /// Print how the avkastningsskatt of this year is calculated from its
+    /// SLR, step by step, then exit
+    #[arg(long, value_name = "YEAR", conflicts_with_all = ["monte_carlo", "find_breakeven", "withdraw", "optimize_switch", "histogram", "trajectory", "compare_tax", "regime_split", "plot"])]
+    explain: Option<i32>,
+
+    /// The tax regimes of --config
+    #[arg(skip)]
+    tax_regimes: BTreeMap<String, TaxRegime>,
+
+    /// Worker threads for the simulation [default: available parallelism]
+    #[arg(long, value_name = "N")]
+    threads: Option<usize>,
+
+    /// Also draw the final values of one holding length as an SVG chart
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["index", "monte_carlo", "find_breakeven"])]
+    plot: Option<PathBuf>,
+
+    /// Holding length to draw with --plot [default: the first of --lengths]
+    #[arg(long, value_name = "YEARS", requires = "plot")]
+    plot_length: Option<i32>,
+
+    /// Print only which account type has the highest average for each
+    /// holding length
+    #[arg(long)]
+    winner_only: bool,
+
+    /// Fractional digits of printed values
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_PRECISION)]
+    precision: usize,
+
+    /// Least width of each value column in the start-year tables, for
+    /// lining up several runs [default: the widest value]
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    table_width: usize,
+
+    /// Field delimiter of the csv format; `tab` gives TSV
+    #[arg(long, value_name = "CHAR", default_value = ",", value_parser = parse_delimiter)]
+    delimiter: char,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+
+    /// TOML file with defaults for the flags above; flags given on the
+    /// command line take precedence
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
 }
 
-fn parse_omxs30_line(line: &str) -> Result<RecordOmxs30, Box<dyn std::error::Error>> {
-    let mut parts = line.split('\t');
+/// A scenario read with `--config`. Keys are the flag names with
+/// underscores, e.g. `kf_fee = 0.5` or `lengths = [10, 20]`.
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Config {
+    omxs30: Option<PathBuf>,
+    append_data: Option<Vec<PathBuf>>,
+    slr: Option<PathBuf>,
+    slr_override: Option<f64>,
+    gross: Option<PathBuf>,
+    index_type: Option<IndexType>,
+    cpi: Option<PathBuf>,
+    year_anchor: Option<YearAnchor>,
+    slr_column: Option<usize>,
+    slr_reference: Option<SlrReference>,
+    slr_delimiter: Option<char>,
+    input_locale: Option<NumberLocale>,
+    compression: Option<Compression>,
+    on_duplicate: Option<OnDuplicate>,
+    fill: Option<Fill>,
+    strict: Option<bool>,
+    from_year: Option<i32>,
+    to_year: Option<i32>,
+    min_value: Option<f64>,
+    max_value: Option<f64>,
+    lengths: Option<Vec<i32>>,
+    exclude_years: Option<Vec<i32>>,
+    exclude_mode: Option<ExcludeMode>,
+    kf_fee: Option<f64>,
+    contribution: Option<f64>,
+    contribution_interval: Option<ContributionInterval>,
+    entry_month: Option<u32>,
+    initial_capital: Option<f64>,
+    real: Option<bool>,
+    base_year: Option<i32>,
+    tax_base_rate: Option<f64>,
+    min_tax_pct: Option<f64>,
+    tax_schedule: Option<PathBuf>,
+    capital_gains_rate: Option<f64>,
+    ak_realize: Option<AkRealize>,
+    dividend_yield: Option<f64>,
+    benchmark_rate: Option<f64>,
+    benchmark_tax: Option<BenchmarkTax>,
+    drag_only_fee: Option<f64>,
+    log_returns: Option<bool>,
+    withdraw: Option<f64>,
+    cache: Option<PathBuf>,
+    currency_label: Option<String>,
+    group_thousands: Option<bool>,
+    table_width: Option<usize>,
+    sort: Option<SortKey>,
+    desc: Option<bool>,
+    tax_regimes: Option<BTreeMap<String, TaxRegime>>,
+}
 
-    let date_str = parts.next().ok_or("Missing date")?;
-    let value_str = parts.next().ok_or("Missing value")?;
+/// Tax parameters of one regime of --compare-tax, e.g.
+/// `[tax_regimes.high]` with `tax_base_rate = 0.35`. Values left out are
+/// those of the run.
+#[derive(Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TaxRegime {
+    tax_base_rate: Option<f64>,
+    min_tax_pct: Option<f64>,
+    capital_gains_rate: Option<f64>,
+    tax_schedule: Option<PathBuf>,
+}
 
-    let value_clean_str = value_str.replace(' ', "").replace(',', ".");
+impl Config {
+    fn read(path: &Path) -> Result<Config, Box<dyn std::error::Error>> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("could not read --config file {}: {e}", path.display()))?;
+        toml::from_str(&text).map_err(|e| format!("--config file {}: {e}", path.display()).into())
+    }
 
-    let date = NaiveDate::parse_from_str(date_str, "%Y-%m-%d")?;
-    let value = f32::from_str(&value_clean_str)?;
+    /// Copies every value set in the file into `args`, except for flags
+    /// that were given on the command line.
+    fn apply(self, args: &mut Args, matches: &ArgMatches) {
+        let from_cli = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+        macro_rules! merge {
+            ($($field:ident),*) => {$(
+                if let Some(value) = self.$field {
+                    if !from_cli(stringify!($field)) {
+                        args.$field = value;
+                    }
+                }
+            )*};
+        }
+        macro_rules! merge_optional {
+            ($($field:ident),*) => {$(
+                if self.$field.is_some() && !from_cli(stringify!($field)) {
+                    args.$field = self.$field;
+                }
+            )*};
+        }
+        merge!(
+            omxs30,
+            append_data,
+            slr,
+            index_type,
+            year_anchor,
+            slr_column,
+            slr_reference,
+            slr_delimiter,
+            input_locale,
+            compression,
+            on_duplicate,
+            fill,
+            strict,
+            lengths,
+            exclude_years,
+            exclude_mode,
+            kf_fee,
+            contribution_interval,
+            entry_month,
+            real,
+            tax_base_rate,
+            min_tax_pct,
+            capital_gains_rate,
+            ak_realize,
+            dividend_yield,
+            benchmark_tax,
+            log_returns,
+            group_thousands,
+            table_width,
+            sort,
+            desc
+        );
+        merge_optional!(
+            slr_override,
+            gross,
+            cpi,
+            contribution,
+            base_year,
+            min_value,
+            max_value,
+            from_year,
+            to_year,
+            tax_schedule,
+            benchmark_rate,
+            drag_only_fee,
+            withdraw,
+            initial_capital,
+            cache,
+            currency_label
+        );
+        // Not a flag, so nothing on the command line overrides it.
+        if let Some(regimes) = self.tax_regimes {
+            args.tax_regimes = regimes;
+        }
+    }
+}
+
+/// What --exclude-years leaves out.
+#[derive(Clone, Copy, PartialEq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ExcludeMode {
+    /// The start years, so no holding period begins in them
+    Start,
+    /// The years' return and avkastningsskatt, in every holding period
+    /// that spans them
+    Compound,
+}
+
+/// How the input files are compressed.
+#[derive(Clone, Copy, PartialEq, Debug, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Compression {
+    /// By the extension: .gz for gzip
+    Auto,
+    None,
+    Gzip,
+}
+
+/// Which OMXS30 series the returns come from.
+#[derive(Clone, Copy, PartialEq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum IndexType {
+    /// Closing prices, with dividends left out or approximated by
+    /// --dividend-yield
+    Price,
+    /// A total-return index with the dividends reinvested, read from --gross
+    Gross,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Text,
+    Csv,
+    Json,
+    /// GitHub-flavored markdown tables
+    Markdown,
+}
+
+/// Parses a 1-based column number of at least `min`.
+fn column_parser(min: usize) -> impl Fn(&str) -> Result<usize, String> + Clone + Send + Sync {
+    move |s| match s.parse::<usize>() {
+        Ok(column) if column >= min => Ok(column),
+        Ok(column) => Err(format!("must be {min} or higher, got {column}")),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// A single character, or `tab` or `\t` for a tab.
+fn parse_delimiter(s: &str) -> Result<char, String> {
+    match s {
+        "tab" | "\\t" => Ok('\t'),
+        _ => {
+            let mut chars = s.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Ok(c),
+                _ => Err(format!("expected a single character or `tab`, got {s:?}")),
+            }
+        }
+    }
+}
+
+fn parse_assert_value(s: &str) -> Result<(NaiveDate, f64), String> {
+    let (date, level) = s
+        .split_once('=')
+        .ok_or_else(|| format!("expected DATE=LEVEL, got {s:?}"))?;
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|e| format!("bad date {date:?}: {e}"))?;
+    let level = level
+        .parse()
+        .map_err(|e| format!("bad level {level:?}: {e}"))?;
+    Ok((date, level))
+}
+
+fn parse_index(s: &str) -> Result<(String, PathBuf), String> {
+    match s.split_once('=') {
+        Some((name, path)) if !name.is_empty() && !path.is_empty() => {
+            Ok((name.to_string(), PathBuf::from(path)))
+        }
+        _ => Err(format!("expected NAME=PATH, got {s:?}")),
+    }
+}
+
+/// The path that makes an input flag read stdin.
+const STDIN_PATH: &str = "-";
 
-    Ok(RecordOmxs30 { date, value })
+/// Holding lengths with fewer start years than this get a warning.
+const MIN_START_YEARS: i32 = 3;
+
+/// Small bundled datasets with one observation per year, used by `--demo`.
+/// Their paths are not real files, so they only need to be unlikely names.
+const DEMO_INPUTS: [(&str, &str); 2] = [
+    ("<demo omxs30>", include_str!("../data/demo_omxs30.txt")),
+    ("<demo slr>", include_str!("../data/demo_slr.csv")),
+];
+
+/// An input file, stdin when its path is [`STDIN_PATH`], or one of the
+/// [`DEMO_INPUTS`].
+enum Input {
+    Stdin(io::StdinLock<'static>),
+    File(io::BufReader<File>),
+    Gzip(Box<io::BufReader<GzDecoder<File>>>),
+    Demo(&'static [u8]),
+    /// The rows of a spreadsheet, as tab-separated lines.
+    Sheet(io::Cursor<Vec<u8>>),
 }
 
-struct RecordSLR {
-    date: NaiveDate,
-    value: f32,
+impl io::Read for Input {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Input::Stdin(stdin) => stdin.read(buf),
+            Input::File(file) => file.read(buf),
+            Input::Gzip(file) => file.read(buf),
+            Input::Demo(data) => data.read(buf),
+            Input::Sheet(rows) => rows.read(buf),
+        }
+    }
+}
+
+impl BufRead for Input {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        match self {
+            Input::Stdin(stdin) => stdin.fill_buf(),
+            Input::File(file) => file.fill_buf(),
+            Input::Gzip(file) => file.fill_buf(),
+            Input::Demo(data) => data.fill_buf(),
+            Input::Sheet(rows) => rows.fill_buf(),
+        }
+    }
+
+    fn consume(&mut self, amount: usize) {
+        match self {
+            Input::Stdin(stdin) => stdin.consume(amount),
+            Input::File(file) => file.consume(amount),
+            Input::Gzip(file) => file.consume(amount),
+            Input::Demo(data) => data.consume(amount),
+            Input::Sheet(rows) => rows.consume(amount),
+        }
+    }
 }
 
-fn parse_slr_line(line: &str) -> Result<RecordSLR, Box<dyn std::error::Error>> {
-    let parts: Vec<&str> = line.split(';').collect();
+/// How every input file is read.
+#[derive(Clone, Copy)]
+struct ReadOptions<'a> {
+    anchor: YearAnchor,
+    on_duplicate: OnDuplicate,
+    fill: Fill,
+    verbose: bool,
+    /// Leave out the warnings and notes about the data.
+    quiet: bool,
+    /// How files are decompressed; stdin and spreadsheets never are.
+    compression: Compression,
+    /// Sheet of spreadsheet inputs; the first one when `None`.
+    sheet: Option<&'a str>,
+    /// Columns (1-based) read from spreadsheet inputs.
+    date_column: usize,
+    value_column: usize,
+}
 
-    if parts.len() < 3 {
-        return Err("Not enough columns".into());
+/// Opens `path`, decompressing it by its extension or as the compression
+/// option says.
+/// Spreadsheets are read whole into tab-separated lines.
+fn open_input(
+    path: &Path,
+    flag: &str,
+    options: ReadOptions,
+) -> Result<Input, Box<dyn std::error::Error>> {
+    if path == Path::new(STDIN_PATH) {
+        return Ok(Input::Stdin(io::stdin().lock()));
+    }
+    if is_spreadsheet(path) {
+        let lines = sheet_lines(
+            path,
+            options.sheet,
+            options.date_column,
+            options.value_column,
+        )
+        .map_err(|e| format!("could not read {flag} file {}: {e}", path.display()))?;
+        return Ok(Input::Sheet(io::Cursor::new(lines.join("\n").into_bytes())));
+    }
+    if let Some((_, data)) = DEMO_INPUTS.iter().find(|(name, _)| path == Path::new(name)) {
+        return Ok(Input::Demo(data.as_bytes()));
     }
+    let extension = path.extension().and_then(|ext| ext.to_str());
+    let compression = match options.compression {
+        Compression::Auto => match extension {
+            Some("gz") => Compression::Gzip,
+            Some("bz2") => {
+                return Err(format!(
+                    "{flag} file {} is bzip2-compressed, which is not supported; \
+                     decompress it with bunzip2 or recompress it with gzip",
+                    path.display()
+                )
+                .into())
+            }
+            _ => Compression::None,
+        },
+        compression => compression,
+    };
+    File::open(path)
+        .map(|file| match compression {
+            Compression::Gzip => Input::Gzip(Box::new(io::BufReader::new(GzDecoder::new(file)))),
+            Compression::Auto | Compression::None => Input::File(io::BufReader::new(file)),
+        })
+        .map_err(|e| format!("could not open {flag} file {}: {e}", path.display()).into())
+}
+
+/// Bumped whenever [`ParsedInputs`] changes, so older caches are reread.
+const CACHE_VERSION: u32 = 1;
+
+/// Every observation of one index and its value for each year.
+type ParsedIndex = (Vec<(NaiveDate, f64)>, BTreeMap<i32, f64>);
+
+/// The inputs as parsed, kept by `--cache`. Combining them is cheap and
+/// depends on the tax flags, so it is redone on every run.
+#[derive(Serialize, Deserialize)]
+struct ParsedInputs {
+    /// The paths and parse options the inputs were read with.
+    key: String,
+    slr_by_year: BTreeMap<i32, f64>,
+    /// One per index, in order.
+    indices: Vec<ParsedIndex>,
+}
 
-    let date_str = parts[0];
-    let value_str = parts[1]; // Third column for "Medelvärde hittills i år"
+/// Loads the cache at `path` if it was written for `key` and none of
+/// `inputs` has been modified since. Anything unreadable counts as stale.
+fn read_cache(path: &Path, key: &str, inputs: &[&Path]) -> Option<ParsedInputs> {
+    let modified = |path: &Path| fs::metadata(path).and_then(|m| m.modified()).ok();
+    let cache_time = modified(path)?;
+    for input in inputs {
+        if modified(input)? > cache_time {
+            return None;
+        }
+    }
+    let bytes = fs::read(path).ok()?;
+    let (parsed, _): (ParsedInputs, _) =
+        bincode::serde::decode_from_slice(&bytes, bincode::config::standard()).ok()?;
+    (parsed.key == key).then_some(parsed)
+}
 
-    let value_clean_str = value_str.replace(' ', "").replace(',', ".");
+fn write_cache(path: &Path, parsed: &ParsedInputs) -> Result<(), String> {
+    let bytes = bincode::serde::encode_to_vec(parsed, bincode::config::standard())
+        .map_err(|e| format!("could not encode --cache file {}: {e}", path.display()))?;
+    fs::write(path, bytes)
+        .map_err(|e| format!("could not write --cache file {}: {e}", path.display()))
+}
 
-    let date = NaiveDate::parse_from_str(date_str, "%Y-%m-%d")?;
-    let value = f32::from_str(&value_clean_str)?;
+/// The flag the index `name` was read for, as named in messages.
+fn index_flag(args: &Args, name: &str, labelled: bool) -> String {
+    if labelled {
+        format!("--index {name}")
+    } else if args.index_type == IndexType::Gross {
+        "--gross".to_string()
+    } else {
+        "--omxs30".to_string()
+    }
+}
 
-    Ok(RecordSLR { date, value })
+/// `path` as listed in the JSON metadata, with its modification time.
+fn input_file(flag: &str, path: &Path) -> InputFile {
+    InputFile {
+        flag: flag.to_string(),
+        path: path.display().to_string(),
+        modified: fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .map(DateTime::<Utc>::from),
+    }
 }
 
-fn calculate_avkastningsskatt(slr: f32) -> f32 {
-    let tax_base_rate = 0.30;
-    let minimum_tax_percentage = 1.25;
+/// Parses the SLR file and every index in `indices`.
+fn read_inputs(
+    args: &Args,
+    indices: &[(String, PathBuf)],
+    labelled: bool,
+    read_options: ReadOptions,
+    key: String,
+) -> Result<ParsedInputs, Box<dyn std::error::Error>> {
+    // Spreadsheet rows are tab separated, with the value in column 2.
+    let (slr_column, slr_delimiter) = if is_spreadsheet(&args.slr) {
+        (2, '\t')
+    } else {
+        (args.slr_column, args.slr_delimiter)
+    };
+    // Spreadsheet cells and the --fetch cache are written by Rust's f64
+    // formatting, with a decimal point, whatever --input-locale is. A
+    // fallback to --omxs30 when fetching fails is a text file like any other.
+    let locale = |path: &Path| {
+        if is_spreadsheet(path) || (args.fetch && path == args.fetch_cache) {
+            Some(NumberLocale::En)
+        } else {
+            Some(args.input_locale)
+        }
+    };
+    let slr_locale = locale(&args.slr);
+    // An --slr-override is filled in for the years of the indices later.
+    let slr_by_year = if args.slr_override.is_some() {
+        BTreeMap::new()
+    } else {
+        read_by_year(
+            &args.slr,
+            "--slr",
+            1,
+            read_options,
+            args.slr_reference,
+            |line| {
+                parse_slr_line(line, slr_column, slr_delimiter, slr_locale)
+                    .map(|r| (r.date, r.value))
+            },
+        )?
+    };
+
+    let mut parsed_indices = Vec::new();
+    for (name, path) in indices {
+        let flag = index_flag(args, name, labelled);
+        let parse =
+            |locale| move |line: &str| parse_omxs30_line(line, locale).map(|r| (r.date, r.value));
+        let mut records = read_records(path, &flag, 0, read_options, parse(locale(path)))?;
+        // --append-data conflicts with --index, so this is the only index.
+        for append in &args.append_data {
+            records.extend(read_records(
+                append,
+                "--append-data",
+                0,
+                read_options,
+                parse(locale(append)),
+            )?);
+        }
+        let by_year = to_by_year(path, &flag, &records, read_options, SlrReference::YearEnd)?;
+        parsed_indices.push((records, by_year));
+    }
+    Ok(ParsedInputs {
+        key,
+        slr_by_year,
+        indices: parsed_indices,
+    })
+}
 
-    0.01 * (slr + 1.0).max(minimum_tax_percentage) * tax_base_rate
+/// Reads the dated values in `path`, after skipping `header_lines`, and
+/// keeps one observation per year.
+fn read_by_year(
+    path: &Path,
+    flag: &str,
+    header_lines: usize,
+    options: ReadOptions,
+    reference: SlrReference,
+    parse: impl Fn(&str) -> Result<(NaiveDate, f64), ParseError>,
+) -> Result<BTreeMap<i32, f64>, Box<dyn std::error::Error>> {
+    let records = read_records(path, flag, header_lines, options, parse)?;
+    to_by_year(path, flag, &records, options, reference)
 }
 
-struct Record {
-    avkastningsskatt: f32,
-    omxs30: f32,
+/// Reads the dated values in `path`, after skipping `header_lines`. Lines
+/// that fail to parse are skipped with a warning unless quiet, listing each
+/// of them in verbose mode.
+fn read_records(
+    path: &Path,
+    flag: &str,
+    header_lines: usize,
+    options: ReadOptions,
+    parse: impl Fn(&str) -> Result<(NaiveDate, f64), ParseError>,
+) -> Result<Vec<(NaiveDate, f64)>, Box<dyn std::error::Error>> {
+    let reader = open_input(path, flag, options)?;
+
+    let mut records: Vec<(NaiveDate, f64)> = Vec::new();
+    let mut total = 0;
+    let mut skipped = 0;
+    let mut first_error = None;
+    for (index, bytes) in reader.split(b'\n').enumerate() {
+        let bytes =
+            bytes.map_err(|e| format!("could not read {flag} file {}: {e}", path.display()))?;
+        if index < header_lines {
+            continue;
+        }
+        // The header is skipped before reading stops at the first line that
+        // is not valid UTF-8, since the SLR export has a Latin-1 header.
+        let Ok(mut line) = String::from_utf8(bytes) else {
+            break;
+        };
+        if index == 0 {
+            if let Some(stripped) = line.strip_prefix('\u{feff}') {
+                line = stripped.to_string();
+            }
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+        total += 1;
+        match parse(&line) {
+            Ok(record) => records.push(record),
+            Err(e) => {
+                skipped += 1;
+                if options.verbose {
+                    eprintln!("{}:{}: skipped {e}", path.display(), index + 1);
+                }
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    if records.is_empty() {
+        return Err(match first_error {
+            Some(e) => format!(
+                "{flag} file {}: no line could be parsed: {e}",
+                path.display()
+            ),
+            None => format!("{flag} file {}: no data", path.display()),
+        }
+        .into());
+    }
+    if skipped > 0 && !options.quiet {
+        eprintln!(
+            "warning: {}: skipped {skipped} of {total} lines",
+            path.display()
+        );
+    }
+    Ok(records)
 }
 
-struct SeriesEntry {
-    start_year: i32,
-    aktiekonto: f32,
-    kapitalförsäkring: f32,
+/// Keeps one row per date and one observation of `records` per year as
+/// chosen by `options` and `reference`, and fills gaps as asked.
+fn to_by_year(
+    path: &Path,
+    flag: &str,
+    records: &[(NaiveDate, f64)],
+    options: ReadOptions,
+    reference: SlrReference,
+) -> Result<BTreeMap<i32, f64>, Box<dyn std::error::Error>> {
+    let records = dedup_dates(records, options.on_duplicate)
+        .map_err(|e| format!("{flag} file {}: {e}, see --on-duplicate", path.display()))?;
+    let mut by_year = values_by_reference(&records, reference, options.anchor);
+    let filled = fill_gaps(&mut by_year, options.fill)
+        .map_err(|e| format!("{flag} file {}: {e}, see --fill", path.display()))?;
+    if !filled.is_empty() && !options.quiet {
+        let years: Vec<String> = filled.iter().map(|y| y.to_string()).collect();
+        eprintln!(
+            "note: {}: filled missing years {}",
+            path.display(),
+            years.join(", ")
+        );
+    }
+    Ok(by_year)
 }
 
-fn print_series(len: i32, series: &[SeriesEntry]) {
-    println!("\n{len} years:");
-    let mut average_aktiekonto = 0.0;
-    let mut average_kapitalförsäkring = 0.0;
-    for e in series {
-        average_aktiekonto += e.aktiekonto;
-        average_kapitalförsäkring += e.kapitalförsäkring;
-        println!(
-            "{}:     {:.2}    {:.2}",
-            e.start_year, e.aktiekonto, e.kapitalförsäkring
+/// Prints a warning for each outlier in the index file `path`, except that
+/// observations outside the analysis `window` are only counted.
+fn warn_outliers(path: &Path, outliers: &[Outlier], window: (i32, i32)) {
+    let mut outside_window = 0;
+    for outlier in outliers {
+        match outlier {
+            Outlier::Date { .. } => outside_window += 1,
+            _ => eprintln!("warning: {}: {outlier}", path.display()),
+        }
+    }
+    if outside_window > 0 {
+        eprintln!(
+            "warning: {}: {outside_window} observations are dated outside {}..={}",
+            path.display(),
+            window.0,
+            window.1
         );
     }
-    println!(
-        "{len} years averages:    {:.2}    {:.2}",
-        average_aktiekonto / series.len() as f32,
-        average_kapitalförsäkring / series.len() as f32
+}
+
+fn read_tax_schedule(path: &Path) -> Result<Vec<TaxPeriod>, Box<dyn std::error::Error>> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| format!("could not read --tax-schedule file {}: {e}", path.display()))?;
+    parse_tax_schedule(&text)
+        .map_err(|e| format!("--tax-schedule file {}: {e}", path.display()).into())
+}
+
+/// Reports the years of `window` that only one of the index at `index_path`
+/// and the SLR at `slr_path` has, which [`combine_records`] would fill with
+/// 0.0. Such years are an error with `--strict`, otherwise a warning that
+/// names the range both files cover.
+fn check_coverage(
+    index_path: &Path,
+    index_by_year: &BTreeMap<i32, f64>,
+    slr_path: &Path,
+    slr_by_year: &BTreeMap<i32, f64>,
+    window: (i32, i32),
+    args: &Args,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (no_slr, no_index) = coverage_gaps(index_by_year, slr_by_year, window);
+    if no_slr.is_empty() && no_index.is_empty() {
+        return Ok(());
+    }
+    let years = |years: &[i32]| {
+        years
+            .iter()
+            .map(|year| year.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let overlap = match common_years([index_by_year, slr_by_year]) {
+        Some((first, last)) => format!("{first}..={last}"),
+        None => "no years".to_string(),
+    };
+    let mut problems = Vec::new();
+    if !no_slr.is_empty() {
+        problems.push(format!(
+            "{} has no SLR for {}",
+            index_path.display(),
+            years(&no_slr)
+        ));
+    }
+    if !no_index.is_empty() {
+        problems.push(format!(
+            "{} has no index value for {}",
+            slr_path.display(),
+            years(&no_index)
+        ));
+    }
+    let message = format!(
+        "{} (the files overlap in {overlap})",
+        problems.join(", and ")
     );
+    if args.strict {
+        return Err(format!("--strict: {message}").into());
+    }
+    if !args.quiet {
+        eprintln!("warning: {message}, using 0.0 for the missing values");
+    }
+    Ok(())
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let mut last_omxs30_by_year: HashMap<i32, f32> = HashMap::new();
-    let mut last_slr_by_year: HashMap<i32, f32> = HashMap::new();
+/// A count of finished steps on stderr, redrawn in place whenever the whole
+/// percentage changes and erased when dropped.
+struct Progress {
+    label: &'static str,
+    done: usize,
+    total: usize,
+    shown: Option<usize>,
+}
 
-    {
-        let path = Path::new("omxs30.txt");
-        let file = File::open(path)?;
-        let reader = io::BufReader::new(file);
-
-        let mut records: Vec<RecordOmxs30> = reader
-            .lines()
-            .map_while(Result::ok)
-            .filter_map(|line| parse_omxs30_line(&line).ok())
-            .collect();
+impl Progress {
+    /// `None` unless stderr is an interactive terminal, as the redrawing
+    /// would only clutter a log file. CI runners that give the job a
+    /// pseudo-terminal set `$CI`, and `TERM=dumb` cannot erase the line.
+    fn new(label: &'static str, total: usize) -> Option<Progress> {
+        let interactive = io::stderr().is_terminal()
+            && env::var_os("CI").is_none()
+            && env::var_os("TERM").is_none_or(|term| term != "dumb");
+        interactive.then_some(Progress {
+            label,
+            done: 0,
+            total,
+            shown: None,
+        })
+    }
 
-        records.sort_by(|a, b| a.date.cmp(&b.date));
+    fn step(&mut self) {
+        self.done += 1;
+        let percent = self.done * 100 / self.total.max(1);
+        if self.shown != Some(percent) {
+            eprint!(
+                "\r{}: {}/{} ({percent}%)",
+                self.label, self.done, self.total
+            );
+            self.shown = Some(percent);
+        }
+    }
+}
 
-        let mut last_records_by_year: HashMap<i32, &RecordOmxs30> = HashMap::new();
-        for record in &records {
-            last_records_by_year.insert(record.date.year(), record);
+impl Drop for Progress {
+    fn drop(&mut self) {
+        if self.shown.is_some() {
+            eprint!("\r\x1b[2K");
         }
+    }
+}
+
+/// How long a downloaded OMXS30 history is reused before fetching again.
+const FETCH_CACHE_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
 
-        for (&year, record) in &last_records_by_year {
-            last_omxs30_by_year.insert(year, record.value);
+/// Returns the file to read OMXS30 values from in `--fetch` mode: the cache
+/// if it is fresh, otherwise a new download written to the cache. When the
+/// download fails, a stale cache or, failing that, `--omxs30` is used.
+fn fetched_omxs30_path(args: &Args) -> PathBuf {
+    let cache = &args.fetch_cache;
+    let cache_age = fs::metadata(cache)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok());
+    if cache_age.is_some_and(|age| age < FETCH_CACHE_MAX_AGE) {
+        return cache.clone();
+    }
+
+    let downloaded = fetch_omxs30().and_then(|records| {
+        let mut out = io::BufWriter::new(File::create(cache)?);
+        for r in &records {
+            writeln!(out, "{}\t{}", r.date, r.value)?;
+        }
+        out.flush()?;
+        Ok(())
+    });
+    match downloaded {
+        Ok(()) => cache.clone(),
+        Err(e) if cache_age.is_some() => {
+            if !args.quiet {
+                eprintln!(
+                    "warning: fetching OMXS30 failed ({e}), using cached {}",
+                    cache.display()
+                );
+            }
+            cache.clone()
+        }
+        Err(e) => {
+            if !args.quiet {
+                eprintln!(
+                    "warning: fetching OMXS30 failed ({e}), using {}",
+                    args.omxs30.display()
+                );
+            }
+            args.omxs30.clone()
         }
     }
+}
 
-    {
-        let path = Path::new("stadslåneränta.csv");
-        let file = File::open(path)?;
-        let reader = io::BufReader::new(file);
-
-        let mut records: Vec<RecordSLR> = reader
-            .lines()
-            .skip(1) // Skip the header
-            .map_while(Result::ok)
-            .filter_map(|line| parse_slr_line(&line).ok())
-            .collect();
+/// The path in the environment variable `name`, unless it is unset or
+/// empty.
+fn env_path(name: &str) -> Option<PathBuf> {
+    env::var_os(name)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
 
-        records.sort_by(|a, b| a.date.cmp(&b.date));
+/// Replaces the `--omxs30` and `--slr` paths with their environment
+/// variables, unless given on the command line, and prints where each path
+/// came from in verbose mode. Runs after `--config` has been applied.
+fn apply_env(args: &mut Args, matches: &ArgMatches) {
+    let verbose = args.verbose;
+    for (id, name, path) in [
+        ("omxs30", OMXS30_ENV, &mut args.omxs30),
+        ("slr", SLR_ENV, &mut args.slr),
+    ] {
+        let source = if matches.value_source(id) == Some(ValueSource::CommandLine) {
+            "the command line".to_string()
+        } else if let Some(env_path) = env_path(name) {
+            *path = env_path;
+            format!("${name}")
+        } else if matches.get_one::<PathBuf>(id) != Some(&*path) {
+            "--config".to_string()
+        } else {
+            "the default".to_string()
+        };
+        if verbose {
+            eprintln!("note: --{id} {} from {source}", path.display());
+        }
+    }
+}
+
+/// Logs to stderr at the level in `RUST_LOG`, `warn` by default, in the
+/// same `warning: ...` style as the other diagnostics.
+fn init_logger() {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn"))
+        .format(|buf, record| {
+            let level = match record.level() {
+                log::Level::Error => "error",
+                log::Level::Warn => "warning",
+                log::Level::Info => "info",
+                log::Level::Debug => "debug",
+                log::Level::Trace => "trace",
+            };
+            writeln!(buf, "{level}: {}", record.args())
+        })
+        .init();
+}
 
-        let mut last_records_by_year: HashMap<i32, &RecordSLR> = HashMap::new();
-        for record in &records {
-            last_records_by_year.insert(record.date.year(), record);
+fn main() -> ExitCode {
+    init_logger();
+    let matches = Args::command().get_matches();
+    match run(&matches, &mut io::stdout().lock()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
         }
+    }
+}
 
-        for (&year, record) in &last_records_by_year {
-            last_slr_by_year.insert(year, record.value);
+/// Runs the command line in `matches`, writing the results to `out`.
+/// Warnings and notes go to stderr.
+fn run(matches: &ArgMatches, out: &mut impl Write) -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = Args::from_arg_matches(matches).unwrap_or_else(|e| e.exit());
+    if let Some(path) = args.config.clone() {
+        Config::read(&path)?.apply(&mut args, matches);
+    }
+    apply_env(&mut args, matches);
+    // clap checks the ranges of flags, but not of values from --config.
+    if !(1..=12).contains(&args.entry_month) {
+        return Err(format!("--entry-month must be 1 to 12, got {}", args.entry_month).into());
+    }
+    if let Some(length) = args.only_length {
+        args.lengths = vec![length];
+    }
+    // Only a config file can leave the lengths empty.
+    if args.lengths.is_empty() {
+        return Err("--lengths must list at least one holding length".into());
+    }
+    if args.real && args.cpi.is_none() {
+        return Err("--real requires --cpi".into());
+    }
+    if args.base_year.is_some() && !args.real {
+        return Err("--base-year requires --real".into());
+    }
+
+    if let Some(threads) = args.threads {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build_global()?;
+    }
+
+    let from_cli = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+    let inputs_given = from_cli("omxs30")
+        || from_cli("slr")
+        || env_path(OMXS30_ENV).is_some()
+        || env_path(SLR_ENV).is_some()
+        || args.fetch
+        || args.gross.is_some()
+        || !args.append_data.is_empty()
+        || !args.index.is_empty();
+    let default_files_present =
+        args.omxs30.exists() && (args.slr_override.is_some() || args.slr.exists());
+    let default_inputs_missing = !(inputs_given || default_files_present);
+    let demo = args.demo || default_inputs_missing;
+    if demo && !args.quiet {
+        if default_inputs_missing {
+            eprintln!(
+                "note: {} or {} not found, using the bundled demo data",
+                args.omxs30.display(),
+                args.slr.display()
+            );
+        }
+        eprintln!("note: results are from the bundled demo data, not a real analysis");
+    }
+    if demo {
+        args.omxs30 = PathBuf::from(DEMO_INPUTS[0].0);
+        args.slr = PathBuf::from(DEMO_INPUTS[1].0);
+    }
+
+    let labelled = !args.index.is_empty();
+    let indices = if args.index_type == IndexType::Gross {
+        let Some(gross) = &args.gross else {
+            return Err("--index-type gross requires --gross".into());
+        };
+        if args.dividend_yield > 0.0 {
+            return Err(
+                "--dividend-yield approximates a gross index, leave it out with --index-type gross"
+                    .into(),
+            );
+        }
+        vec![("OMXS30".to_string(), gross.clone())]
+    } else if labelled {
+        args.index.clone()
+    } else if args.fetch {
+        vec![("OMXS30".to_string(), fetched_omxs30_path(&args))]
+    } else {
+        vec![("OMXS30".to_string(), args.omxs30.clone())]
+    };
+    for (i, (name, _)) in indices.iter().enumerate() {
+        if indices[..i].iter().any(|(other, _)| other == name) {
+            return Err(format!("--index {name} is given more than once").into());
         }
     }
 
-    let mut combined_records = HashMap::new();
-    let years: HashSet<_> = last_omxs30_by_year
-        .keys()
-        .chain(last_slr_by_year.keys())
+    let mut inputs: Vec<&Path> = indices.iter().map(|(_, path)| path.as_path()).collect();
+    inputs.extend(args.append_data.iter().map(PathBuf::as_path));
+    if args.slr_override.is_none() {
+        inputs.push(&args.slr);
+    }
+    // The inputs read into the cache, which leaves out the CPI.
+    let cached_inputs = inputs.len();
+    if args.real {
+        inputs.extend(args.cpi.as_deref());
+    }
+    if inputs
+        .iter()
+        .filter(|path| **path == Path::new(STDIN_PATH))
+        .count()
+        > 1
+    {
+        return Err(format!("only one input can be read from stdin ({STDIN_PATH})").into());
+    }
+
+    if args.date_column == 0 || args.value_column == 0 {
+        return Err("--date-column and --value-column count from 1".into());
+    }
+    if args.date_column == args.value_column {
+        return Err("--date-column and --value-column must differ".into());
+    }
+    if args.slr_column < 2 {
+        return Err("--slr-column must be 2 or higher, column 1 is the date".into());
+    }
+    let read_options = ReadOptions {
+        anchor: args.year_anchor,
+        on_duplicate: args.on_duplicate,
+        fill: args.fill,
+        verbose: args.verbose,
+        quiet: args.quiet,
+        compression: if args.gzip {
+            Compression::Gzip
+        } else {
+            args.compression
+        },
+        sheet: args.sheet.as_deref(),
+        date_column: args.date_column,
+        value_column: args.value_column,
+    };
+    let cache_key = format!(
+        "{CACHE_VERSION} {indices:?} {:?} {:?} {} {:?} {:?} {:?} {:?} {:?} {:?} {:?} {:?} {} {}",
+        args.append_data,
+        args.slr_override.is_none().then_some(&args.slr),
+        args.slr_column,
+        args.slr_reference,
+        args.slr_delimiter,
+        args.input_locale,
+        args.year_anchor,
+        args.on_duplicate,
+        args.fill,
+        read_options.compression,
+        args.sheet,
+        args.date_column,
+        args.value_column
+    );
+    let cached = match &args.cache {
+        Some(path) if inputs.contains(&Path::new(STDIN_PATH)) => {
+            return Err(
+                format!("--cache {} cannot be used with stdin input", path.display()).into(),
+            )
+        }
+        Some(path) => read_cache(path, &cache_key, &inputs[..cached_inputs]),
+        None => None,
+    };
+    let parsed = match cached {
+        Some(parsed) => {
+            if args.verbose {
+                let path = args.cache.as_deref().unwrap_or(Path::new(""));
+                eprintln!("note: read the inputs from --cache {}", path.display());
+            }
+            parsed
+        }
+        None => {
+            let parsed = read_inputs(&args, &indices, labelled, read_options, cache_key)?;
+            if let Some(path) = &args.cache {
+                write_cache(path, &parsed)?;
+            }
+            parsed
+        }
+    };
+
+    let schedule = match &args.tax_schedule {
+        Some(path) => read_tax_schedule(path)?,
+        None => Vec::new(),
+    };
+    let tax = TaxParams {
+        tax_base_rate: args.tax_base_rate,
+        minimum_tax_percentage: args.min_tax_pct,
+        schedule,
+    };
+
+    let indices_by_year: Vec<_> = indices
+        .iter()
+        .zip(parsed.indices)
+        .map(|((name, path), (records, by_year))| (name, path, records, by_year))
         .collect();
+    if args.assert_tolerance.is_nan() || args.assert_tolerance < 0.0 {
+        return Err("--assert-tolerance must not be negative".into());
+    }
+    let mut mismatches = Vec::new();
+    for (name, _, records, _) in &indices_by_year {
+        let prefix = if labelled {
+            format!("{name}: ")
+        } else {
+            String::new()
+        };
+        for &(date, level) in &args.assert_value {
+            match records.iter().rev().find(|(d, _)| *d == date) {
+                Some(&(_, value)) if (value - level).abs() <= args.assert_tolerance => {}
+                Some(&(_, value)) => {
+                    mismatches.push(format!("{prefix}{date}: expected {level}, got {value}"))
+                }
+                None => mismatches.push(format!("{prefix}{date}: expected {level}, got no value")),
+            }
+        }
+    }
+    if !mismatches.is_empty() {
+        return Err(format!("--assert-value failed:\n  {}", mismatches.join("\n  ")).into());
+    }
 
-    for year in years {
-        let omxs30 = *last_omxs30_by_year.get(year).unwrap_or(&0.0);
-        let slr = *last_slr_by_year.get(year).unwrap_or(&0.0);
+    let last_slr_by_year = match args.slr_override {
+        Some(rate) => indices_by_year
+            .iter()
+            .flat_map(|(_, _, _, by_year)| by_year.keys())
+            .map(|&year| (year, rate))
+            .collect(),
+        None => parsed.slr_by_year,
+    };
 
-        combined_records.insert(
-            year,
-            Record {
-                avkastningsskatt: calculate_avkastningsskatt(slr),
-                omxs30,
-            },
+    if let Some(year) = args.explain {
+        if !matches!(args.format, Format::Text) {
+            return Err("--explain only supports text output".into());
+        }
+        let Some(&slr) = last_slr_by_year.get(&year) else {
+            let source = match args.slr_override {
+                Some(_) => "--slr-override".to_string(),
+                None => args.slr.display().to_string(),
+            };
+            return Err(format!("--explain {year}: {source} has no SLR for {year}").into());
+        };
+        let explanation = explain_avkastningsskatt(year, slr, &tax);
+        write_tax_explanation(out, &explanation, args.precision)?;
+        return Ok(());
+    }
+
+    let data_years = common_years(
+        indices_by_year
+            .iter()
+            .map(|(_, _, _, by_year)| by_year)
+            .chain([&last_slr_by_year]),
+    );
+    let (from_year, to_year) = match (args.from_year, args.to_year, data_years) {
+        (Some(from), Some(to), _) => (from, to),
+        (from, to, Some((first, last))) => (from.unwrap_or(first), to.unwrap_or(last)),
+        (_, _, None) => {
+            return Err("the inputs have no years in common, set --from-year and --to-year".into())
+        }
+    };
+    if from_year >= to_year {
+        return Err(
+            format!("--from-year ({from_year}) must be before --to-year ({to_year})").into(),
         );
     }
 
-    let mut series_5 = Vec::new();
-    let mut series_10 = Vec::new();
-    let mut series_15 = Vec::new();
-    let mut series_20 = Vec::new();
-    let mut series_25 = Vec::new();
+    if args.tax_schedule.is_some() && !args.quiet {
+        let uncovered: Vec<String> = (from_year..=to_year)
+            .filter(|&year| tax.period(year).is_none())
+            .map(|year| year.to_string())
+            .collect();
+        if !uncovered.is_empty() {
+            eprintln!(
+                "note: --tax-schedule does not cover {}, using the built-in rule",
+                uncovered.join(", ")
+            );
+        }
+    }
 
-    for start_year in 1993..=2022 {
-        let mut kf_sum = 1.0;
-        let mut ak_sum = 1.0;
+    let limits = Limits {
+        min_value: args.min_value,
+        max_value: args.max_value,
+        window: Some((from_year, to_year)),
+    };
+    let mut index_records = Vec::new();
+    for (name, path, records, last_index_by_year) in &indices_by_year {
+        if !args.quiet {
+            warn_outliers(
+                path,
+                &find_outliers(records, last_index_by_year, &limits),
+                (from_year, to_year),
+            );
+        }
+        if args.slr_override.is_none() {
+            check_coverage(
+                path,
+                last_index_by_year,
+                &args.slr,
+                &last_slr_by_year,
+                (from_year, to_year),
+                &args,
+            )?;
+        }
+        let mut combined_records = combine_records(last_index_by_year, &last_slr_by_year, &tax);
+        if args.exclude_mode == ExcludeMode::Compound {
+            neutralize_years(&mut combined_records, &args.exclude_years);
+        }
+        if args.dump_combined {
+            // Missing years are what the dump is for, so they are not errors.
+            index_records.push((name.to_string(), combined_records));
+            continue;
+        }
 
-        for year in (start_year + 1)..=2023 {
-            let previous_val = combined_records[&(year - 1)].omxs30;
-            let val = combined_records[&year].omxs30;
-            let diff = val / previous_val;
-            ak_sum *= diff;
-            kf_sum *= diff;
-            kf_sum *= 1.0 - combined_records[&year].avkastningsskatt;
+        let missing_years: Vec<String> = (from_year..=to_year)
+            .filter(|year| !combined_records.contains_key(year))
+            .map(|year| year.to_string())
+            .collect();
+        if !missing_years.is_empty() {
+            let prefix = if labelled {
+                format!("{name}: ")
+            } else {
+                String::new()
+            };
+            return Err(format!("{prefix}no data for years: {}", missing_years.join(", ")).into());
+        }
+        check_index_values(&combined_records, (from_year, to_year))?;
+        index_records.push((name.to_string(), combined_records));
+    }
 
-            let ak_val = ak_sum - ((ak_sum - 1.0) * 0.206).max(0.0);
+    if args.dump_combined {
+        match args.format {
+            Format::Text => write_combined_text(out, &index_records, labelled)?,
+            Format::Csv => write_combined_csv(out, &index_records, labelled, args.delimiter)?,
+            Format::Json => write_combined_json(out, &index_records, labelled)?,
+            Format::Markdown => write_combined_markdown(out, &index_records, labelled)?,
+        }
+        return Ok(());
+    }
 
-            let year_count = year - start_year;
+    let window = to_year - from_year;
+    // --find-breakeven sweeps every length in the window instead.
+    let requested_lengths = if args.find_breakeven {
+        &[][..]
+    } else {
+        &args.lengths[..]
+    };
+    let what = if args.only_length.is_some() {
+        "--only-length"
+    } else {
+        "holding length"
+    };
+    // Start years dropped by --exclude-years do not count towards the
+    // minimum; those of --exclude-mode compound are still simulated.
+    let excluded_start_years: &[i32] = match args.exclude_mode {
+        ExcludeMode::Start => &args.exclude_years,
+        ExcludeMode::Compound => &[],
+    };
+    let start_year_count = |length: i32| {
+        (from_year..=to_year - length)
+            .filter(|year| !excluded_start_years.contains(year))
+            .count() as i32
+    };
+    for &length in requested_lengths {
+        if length < 1 || length > window {
+            return Err(format!(
+                "{what} {length} does not fit in {}..={} (must be 1 to {window} years)",
+                from_year, to_year
+            )
+            .into());
+        }
+        let start_years = start_year_count(length);
+        if start_years < MIN_START_YEARS && !args.quiet {
+            eprintln!(
+                "warning: {what} {length} has only {start_years} start {} in {from_year}..={to_year}{}, too few for meaningful averages, leaving it out",
+                if start_years == 1 { "year" } else { "years" },
+                if excluded_start_years.is_empty() { "" } else { " after --exclude-years" }
+            );
+        }
+    }
+    if !args.find_breakeven {
+        args.lengths
+            .retain(|&length| start_year_count(length) >= MIN_START_YEARS);
+        if args.lengths.is_empty() {
+            return Err(format!(
+                "no holding length has {MIN_START_YEARS} or more start years in {from_year}..={to_year}, use shorter lengths or a longer range"
+            )
+            .into());
+        }
+    }
 
-            if year_count == 5 {
-                series_5.push(SeriesEntry {
-                    start_year,
-                    aktiekonto: ak_val,
-                    kapitalförsäkring: kf_sum,
-                });
+    if args.kf_fee < 0.0 || args.kf_fee >= 100.0 {
+        return Err(format!("--kf-fee must be between 0 and 100, got {}", args.kf_fee).into());
+    }
+    if args.contribution.is_some_and(|amount| amount <= 0.0) {
+        return Err("--contribution must be positive".into());
+    }
+    if args
+        .initial_capital
+        .is_some_and(|amount| !(amount.is_finite() && amount > 0.0))
+    {
+        return Err("--initial-capital must be positive".into());
+    }
+    if args.initial_capital.is_some() && args.contribution.is_some() {
+        return Err("--initial-capital cannot be combined with --contribution".into());
+    }
+    // Multipliers are left as they are.
+    let absolute = args.initial_capital.is_some() || args.contribution.is_some();
+    if (args.currency_label.is_some() || args.group_thousands) && !absolute {
+        return Err(
+            "--currency-label and --group-thousands need --initial-capital or --contribution"
+                .into(),
+        );
+    }
+    let amounts = Amounts {
+        currency_label: args.currency_label.clone(),
+        group_thousands: args.group_thousands,
+        table_width: args.table_width,
+    };
+    for (flag, rate) in [
+        ("--tax-base-rate", args.tax_base_rate),
+        ("--capital-gains-rate", args.capital_gains_rate),
+    ] {
+        if !(0.0..=1.0).contains(&rate) {
+            return Err(format!("{flag} must be between 0 and 1, got {rate}").into());
+        }
+    }
+    if !(0.0..100.0).contains(&args.dividend_yield) {
+        return Err(format!(
+            "--dividend-yield must be between 0 and 100, got {}",
+            args.dividend_yield
+        )
+        .into());
+    }
+    if args
+        .drag_only_fee
+        .is_some_and(|fee| !(0.0..100.0).contains(&fee))
+    {
+        return Err("--drag-only-fee must be between 0 and 100".into());
+    }
+    if args.benchmark_rate.is_some_and(|rate| rate <= -100.0) {
+        return Err("--benchmark-rate must be above -100".into());
+    }
+    if args.min_tax_pct < 0.0 {
+        return Err(format!(
+            "--min-tax-pct must not be negative, got {}",
+            args.min_tax_pct
+        )
+        .into());
+    }
+    let params = SimulationParams {
+        kf_fee: args.kf_fee,
+        contribution: args.contribution.map(|amount| ContributionPlan {
+            amount,
+            interval: args.contribution_interval,
+        }),
+        capital_gains_rate: args.capital_gains_rate,
+        ak_realize: args.ak_realize,
+        dividend_yield: args.dividend_yield,
+        entry_month: args.entry_month,
+        initial_capital: args.initial_capital,
+        drag_only_fee: args.drag_only_fee,
+        log_returns: args.log_returns,
+        excluded_start_years: match args.exclude_mode {
+            ExcludeMode::Start => args.exclude_years.clone(),
+            ExcludeMode::Compound => Vec::new(),
+        },
+        benchmark: args.benchmark_rate.map(|rate| Benchmark {
+            rate,
+            tax: args.benchmark_tax,
+        }),
+    };
+
+    if demo && matches!(args.format, Format::Text) {
+        writeln!(
+            out,
+            "DEMO DATA: bundled sample with one value per year, not a real analysis"
+        )?;
+    }
+
+    if !args.exclude_years.is_empty() {
+        let years: Vec<String> = args.exclude_years.iter().map(|y| y.to_string()).collect();
+        let note = match args.exclude_mode {
+            ExcludeMode::Start => format!("Start years left out: {}", years.join(", ")),
+            ExcludeMode::Compound => format!(
+                "Years counted without a return or tax: {}",
+                years.join(", ")
+            ),
+        };
+        // The CSV and JSON output has no room for notes.
+        if matches!(args.format, Format::Text) {
+            writeln!(out, "{note}")?;
+        } else if !args.quiet {
+            eprintln!("note: {note}");
+        }
+    }
+
+    if args.seed.is_some() && args.monte_carlo.is_none() && args.bootstrap.is_none() {
+        return Err("--seed requires --monte-carlo or --bootstrap".into());
+    }
+    if args.bootstrap == Some(0) {
+        return Err("--bootstrap needs at least one resample".into());
+    }
+
+    if let Some(paths) = args.monte_carlo {
+        if paths == 0 {
+            return Err("--monte-carlo needs at least one path".into());
+        }
+        if !matches!(args.format, Format::Text) {
+            return Err("--monte-carlo only supports text output".into());
+        }
+        let seed = args.seed.unwrap_or_else(|| rand::rng().random());
+        writeln!(
+            out,
+            "Monte Carlo with {paths} paths per length, seed {seed}"
+        )?;
+        let total = paths * args.lengths.len() * index_records.len();
+        let mut progress = Progress::new("Monte Carlo paths", total).filter(|_| !args.quiet);
+        let summaries: Vec<_> = index_records
+            .iter()
+            .map(|(name, combined_records)| {
+                // Every index gets the same draws for a given seed.
+                let mut rng = StdRng::seed_from_u64(seed);
+                let results = monte_carlo(
+                    combined_records,
+                    &args.lengths,
+                    (from_year, to_year),
+                    &params,
+                    paths,
+                    &mut rng,
+                    || progress.iter_mut().for_each(Progress::step),
+                )?;
+                Ok((name, results))
+            })
+            .collect::<Result<_, String>>()?;
+        // Clears the progress line before the results are printed.
+        drop(progress);
+        for (name, results) in &summaries {
+            if labelled {
+                write_index_header(out, name)?;
+            }
+            for r in results {
+                write_monte_carlo(out, r, args.precision)?;
+            }
+        }
+        return Ok(());
+    }
+
+    if let Some(rate) = args.withdraw {
+        if rate <= 0.0 || rate > 100.0 {
+            return Err(format!("--withdraw must be above 0 and at most 100, got {rate}").into());
+        }
+        if !matches!(args.format, Format::Text) {
+            return Err("--withdraw only supports text output".into());
+        }
+        for (name, combined_records) in &index_records {
+            if labelled {
+                write_index_header(out, name)?;
+            }
+            let withdrawals = simulate_withdrawals(
+                combined_records,
+                &args.lengths,
+                (from_year, to_year),
+                &params,
+                rate,
+            )?;
+            for &length in &args.lengths {
+                let entries = withdrawals.get(&length).map_or(&[][..], Vec::as_slice);
+                write_withdrawals(out, length, rate, entries, args.precision)?;
+            }
+        }
+        return Ok(());
+    }
+
+    if args.optimize_switch {
+        if !matches!(args.format, Format::Text) {
+            return Err("--optimize-switch only supports text output".into());
+        }
+        for (name, combined_records) in &index_records {
+            if labelled {
+                write_index_header(out, name)?;
+            }
+            let switches = optimize_switch(
+                combined_records,
+                &args.lengths,
+                (from_year, to_year),
+                &params,
+            )?;
+            for &length in &args.lengths {
+                let entries = switches.get(&length).map_or(&[][..], Vec::as_slice);
+                write_switches(out, length, entries, args.precision, &amounts)?;
+            }
+        }
+        return Ok(());
+    }
+
+    if let Some(start_year) = args.trajectory {
+        if !(from_year..to_year).contains(&start_year) {
+            return Err(format!(
+                "--trajectory {start_year} is not a start year in {from_year}..{to_year}"
+            )
+            .into());
+        }
+        if !matches!(args.format, Format::Text) {
+            return Err("--trajectory only supports text output".into());
+        }
+        for (name, combined_records) in &index_records {
+            if labelled {
+                write_index_header(out, name)?;
+            }
+            let years = trajectory(combined_records, start_year, to_year, &params)?;
+            write_trajectory(out, start_year, &years, args.precision)?;
+        }
+        return Ok(());
+    }
+
+    if !args.compare_tax.is_empty() {
+        if !matches!(args.format, Format::Text) {
+            return Err("--compare-tax only supports text output".into());
+        }
+        let mut regimes = Vec::new();
+        for name in &args.compare_tax {
+            let regime = match args.tax_regimes.get(name) {
+                Some(regime) => regime.clone(),
+                None if name == "current" => TaxRegime::default(),
+                None => {
+                    return Err(format!(
+                        "--compare-tax {name}: no such regime in the tax_regimes of --config"
+                    )
+                    .into())
+                }
+            };
+            for (key, rate) in [
+                ("tax_base_rate", regime.tax_base_rate),
+                ("capital_gains_rate", regime.capital_gains_rate),
+            ] {
+                if rate.is_some_and(|rate| !(0.0..=1.0).contains(&rate)) {
+                    return Err(format!("tax regime {name}: {key} must be between 0 and 1").into());
+                }
+            }
+            if regime.min_tax_pct.is_some_and(|pct| pct < 0.0) {
+                return Err(format!("tax regime {name}: min_tax_pct must not be negative").into());
+            }
+            let regime_tax = TaxParams {
+                tax_base_rate: regime.tax_base_rate.unwrap_or(tax.tax_base_rate),
+                minimum_tax_percentage: regime.min_tax_pct.unwrap_or(tax.minimum_tax_percentage),
+                schedule: match &regime.tax_schedule {
+                    Some(path) => read_tax_schedule(path)?,
+                    None => tax.schedule.clone(),
+                },
+            };
+            let config = RunConfig {
+                lengths: args.lengths.clone(),
+                range: (from_year, to_year),
+                params: SimulationParams {
+                    capital_gains_rate: regime
+                        .capital_gains_rate
+                        .unwrap_or(params.capital_gains_rate),
+                    ..params.clone()
+                },
+            };
+            regimes.push((name.as_str(), regime_tax, config));
+        }
+
+        for (name, _, _, last_index_by_year) in &indices_by_year {
+            if labelled {
+                write_index_header(out, name)?;
             }
+            let rows = regimes
+                .iter()
+                .map(|(regime, regime_tax, config)| {
+                    let records =
+                        combine_records(last_index_by_year, &last_slr_by_year, regime_tax);
+                    Ok((*regime, kf_vs_ak::run(config, &records)?))
+                })
+                .collect::<Result<Vec<(&str, Results)>, String>>()?;
+            write_tax_comparison(out, &args.lengths, &rows, args.precision)?;
+        }
+        return Ok(());
+    }
 
-            if year_count == 10 {
-                series_10.push(SeriesEntry {
-                    start_year,
-                    aktiekonto: ak_val,
-                    kapitalförsäkring: kf_sum,
-                });
+    if let Some(change_year) = args.regime_split {
+        if !matches!(args.format, Format::Text) {
+            return Err("--regime-split only supports text output".into());
+        }
+        for (name, combined_records) in &index_records {
+            if labelled {
+                write_index_header(out, name)?;
             }
+            let series = simulate(
+                combined_records,
+                &args.lengths,
+                (from_year, to_year),
+                &params,
+            )?;
+            let groups = regime_split(&series, change_year);
+            write_regime_split(out, change_year, &groups, args.precision)?;
+        }
+        return Ok(());
+    }
 
-            if year_count == 15 {
-                series_15.push(SeriesEntry {
-                    start_year,
-                    aktiekonto: ak_val,
-                    kapitalförsäkring: kf_sum,
-                });
+    if let Some(length) = args.histogram {
+        if !(1..=window).contains(&length) {
+            return Err(format!(
+                "--histogram {length} does not fit in {}..={} (must be 1 to {window} years)",
+                from_year, to_year
+            )
+            .into());
+        }
+        if !(args.bucket_width.is_finite() && args.bucket_width > 0.0) {
+            return Err(
+                format!("--bucket-width must be positive, got {}", args.bucket_width).into(),
+            );
+        }
+        if !matches!(args.format, Format::Text) {
+            return Err("--histogram only supports text output".into());
+        }
+        for (name, combined_records) in &index_records {
+            if labelled {
+                write_index_header(out, name)?;
             }
+            let series = simulate(combined_records, &[length], (from_year, to_year), &params)?;
+            let entries = series.get(&length).map_or(&[][..], Vec::as_slice);
+            write_histogram(out, length, entries, args.bucket_width, args.precision)?;
+        }
+        return Ok(());
+    }
 
-            if year_count == 20 {
-                series_20.push(SeriesEntry {
-                    start_year,
-                    aktiekonto: ak_val,
-                    kapitalförsäkring: kf_sum,
-                });
+    if args.find_breakeven {
+        let all_lengths: Vec<i32> = (1..=window).collect();
+        let results: Vec<IndexAverages> = index_records
+            .iter()
+            .map(|(name, combined_records)| {
+                let series = simulate(
+                    combined_records,
+                    &all_lengths,
+                    (from_year, to_year),
+                    &params,
+                )?;
+                Ok((name.clone(), length_averages(&series)))
+            })
+            .collect::<Result<_, String>>()?;
+        let range = (from_year, to_year);
+        match args.format {
+            Format::Text => write_breakeven_text(out, &results, labelled, range, args.precision)?,
+            Format::Csv => write_breakeven_csv(out, &results, labelled, args.delimiter)?,
+            Format::Json => write_breakeven_json(out, &results, labelled)?,
+            Format::Markdown => {
+                write_breakeven_markdown(out, &results, labelled, range, args.precision)?
             }
+        }
+        return Ok(());
+    }
+
+    let cpi_by_year = match args.cpi.as_deref() {
+        Some(cpi_path) if args.real && is_spreadsheet(cpi_path) => {
+            return Err("--cpi does not read spreadsheets, export it as CSV".into());
+        }
+        Some(cpi_path) if args.real => Some(read_by_year(
+            cpi_path,
+            "--cpi",
+            1,
+            read_options,
+            SlrReference::YearEnd,
+            |line| parse_cpi_line(line, Some(args.input_locale)).map(|r| (r.date, r.value)),
+        )?),
+        _ => None,
+    };
+
+    let run_simulation = |combined_records, params: &SimulationParams| {
+        let config = RunConfig {
+            lengths: args.lengths.clone(),
+            range: (from_year, to_year),
+            params: params.clone(),
+        };
+        let mut results = kf_vs_ak::run(&config, combined_records)?;
+        if let Some(cpi_by_year) = &cpi_by_year {
+            results.deflate(cpi_by_year, args.base_year)?;
+        }
+        Ok::<_, String>(results)
+    };
+    let mut results: Vec<IndexSeries> = Vec::new();
+    for (name, combined_records) in &index_records {
+        results.push((name.clone(), run_simulation(combined_records, &params)?));
+    }
+    // With yearly realization, the same simulation with the tax deferred to
+    // the end shows what the deferral is worth.
+    let deferred_params = SimulationParams {
+        ak_realize: AkRealize::Sell,
+        ..params.clone()
+    };
+    let deferred: Vec<Results> = if params.ak_realize == AkRealize::Annual
+        && matches!(args.format, Format::Text)
+        && !args.winner_only
+    {
+        index_records
+            .iter()
+            .map(|(_, combined_records)| run_simulation(combined_records, &deferred_params))
+            .collect::<Result<_, _>>()?
+    } else {
+        Vec::new()
+    };
+
+    let bootstrap = args
+        .bootstrap
+        .map(|resamples| (resamples, args.seed.unwrap_or_else(|| rand::rng().random())));
+    if let Some((resamples, seed)) = bootstrap {
+        for (_, results) in &mut results {
+            // Every index gets the same draws for a given seed.
+            results.bootstrap(resamples, &mut StdRng::seed_from_u64(seed));
+        }
+    }
 
-            if year_count == 25 {
-                series_25.push(SeriesEntry {
-                    start_year,
-                    aktiekonto: ak_val,
-                    kapitalförsäkring: kf_sum,
-                });
+    if let Some(plot_path) = &args.plot {
+        let length = args
+            .plot_length
+            .or(args.lengths.first().copied())
+            .ok_or("--plot needs --plot-length or at least one of --lengths")?;
+        let series = results[0]
+            .1
+            .series
+            .get(&length)
+            .ok_or_else(|| format!("--plot-length {length} is not one of --lengths"))?;
+        plot_series(plot_path, length, series)?;
+    }
+    // Sorted after plotting, which draws the start years in order.
+    if args.sort != SortKey::Year || args.desc {
+        for (_, series) in &mut results {
+            for entries in series.series.values_mut() {
+                sort_entries(entries, args.sort, args.desc);
             }
         }
     }
 
-    print_series(5, &series_5);
-    print_series(10, &series_10);
-    print_series(15, &series_15);
-    print_series(20, &series_20);
-    print_series(25, &series_25);
+    match args.format {
+        Format::Text => {
+            if let Some((resamples, seed)) = bootstrap {
+                writeln!(
+                    out,
+                    "Intervals are from {resamples} bootstrap resamples of the start years, seed {seed}"
+                )?;
+            }
+            if params.kf_fee > 0.0 {
+                writeln!(
+                    out,
+                    "KF values include an annual fee of {:.p$}%",
+                    params.kf_fee,
+                    p = args.precision
+                )?;
+            }
+            if params.dividend_yield > 0.0 {
+                writeln!(
+                    out,
+                    "Returns include a flat {:.p$}% dividend yield, taxed yearly on the AK",
+                    params.dividend_yield,
+                    p = args.precision
+                )?;
+            }
+            if args.index_type == IndexType::Gross {
+                writeln!(
+                    out,
+                    "Returns are from the gross index, with dividends reinvested"
+                )?;
+            }
+            if let Some(rate) = args.slr_override {
+                writeln!(
+                    out,
+                    "The statslåneränta is {rate:.p$}% in every year",
+                    p = args.precision
+                )?;
+            }
+            if let Some(fee) = params.drag_only_fee {
+                writeln!(
+                    out,
+                    "The column after ISK is an untaxed account paying only a {fee:.p$}% annual fee",
+                    p = args.precision
+                )?;
+            }
+            match args.slr_reference {
+                SlrReference::Nov30 => writeln!(
+                    out,
+                    "The avkastningsskatt uses the SLR of November 30 the year before"
+                )?,
+                SlrReference::Average => writeln!(
+                    out,
+                    "The avkastningsskatt uses the average SLR of each year"
+                )?,
+                SlrReference::YearEnd => {}
+            }
+            if params.log_returns {
+                writeln!(out, "Sharpe ratios are from log returns")?;
+            }
+            if let Some(benchmark) = params.benchmark {
+                let taxed = match benchmark.tax {
+                    BenchmarkTax::None => "untaxed",
+                    BenchmarkTax::Schablon => "schablon taxed",
+                };
+                writeln!(
+                    out,
+                    "The last column is a savings account at {:.p$}% a year, {taxed}",
+                    benchmark.rate,
+                    p = args.precision
+                )?;
+            }
+            if params.entry_month > 1 {
+                writeln!(
+                    out,
+                    "Invested in month {} of the first year, whose return and tax are prorated",
+                    params.entry_month
+                )?;
+            }
+            match params.ak_realize {
+                AkRealize::Sell => {}
+                AkRealize::Hold => writeln!(
+                    out,
+                    "AK values are before capital-gains tax (position held)"
+                )?,
+                AkRealize::Annual => writeln!(
+                    out,
+                    "AK gains are taxed every year (sold and rebought annually)"
+                )?,
+            }
+            if args.real {
+                match args.base_year {
+                    Some(year) => writeln!(out, "Values are in {year} kronor (CPI adjusted)")?,
+                    None => writeln!(out, "Values are real, in kronor of each start year")?,
+                }
+            }
+            if let Some(capital) = params.initial_capital {
+                writeln!(
+                    out,
+                    "Values are account totals after investing {capital} up front"
+                )?;
+            }
+            if let Some(plan) = params.contribution {
+                let interval = match plan.interval {
+                    ContributionInterval::Yearly => "year",
+                    ContributionInterval::Monthly => "month",
+                };
+                writeln!(
+                    out,
+                    "Values are account totals after contributing {} every {interval}",
+                    plan.amount
+                )?;
+            }
+            for (i, (name, series)) in results.iter().enumerate() {
+                if labelled {
+                    write_index_header(out, name)?;
+                }
+                if args.winner_only {
+                    for &length in &args.lengths {
+                        write_winner(
+                            out,
+                            length,
+                            &series.summaries[&length],
+                            args.precision,
+                            &amounts,
+                        )?;
+                    }
+                } else {
+                    write_text(out, &args.lengths, series, args.precision, &amounts)?;
+                }
+                if let Some(deferred) = deferred.get(i) {
+                    write_deferral(
+                        out,
+                        &args.lengths,
+                        &series.series,
+                        &deferred.series,
+                        args.precision,
+                    )?;
+                }
+            }
+        }
+        Format::Csv => write_csv(out, &args.lengths, &results, labelled, args.delimiter)?,
+        Format::Json => {
+            let mut inputs: Vec<InputFile> = indices
+                .iter()
+                .map(|(name, path)| input_file(&index_flag(&args, name, labelled), path))
+                .collect();
+            inputs.extend(
+                args.append_data
+                    .iter()
+                    .map(|path| input_file("--append-data", path)),
+            );
+            if args.slr_override.is_none() {
+                inputs.push(input_file("--slr", &args.slr));
+            }
+            if args.real {
+                inputs.extend(args.cpi.as_deref().map(|path| input_file("--cpi", path)));
+            }
+            inputs.extend(
+                args.tax_schedule
+                    .as_deref()
+                    .map(|path| input_file("--tax-schedule", path)),
+            );
+            let meta = RunMeta {
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                generated_at: Utc::now(),
+                from_year,
+                to_year,
+                inputs,
+                tax: tax.clone(),
+                simulation: params.clone(),
+            };
+            write_json(out, &args.lengths, &results, labelled, &meta)?
+        }
+        Format::Markdown => write_markdown(
+            out,
+            &args.lengths,
+            &results,
+            labelled,
+            args.precision,
+            &amounts,
+        )?,
+    }
 
     Ok(())
 }
@@ -0,0 +1,58 @@
+use crate::simulate::{Accounts, Record, SimulationParams, YearStep};
+use crate::stats::percentile;
+use rand::{Rng, RngExt};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Distribution of the KF-minus-AK final value over bootstrapped paths.
+#[derive(Debug, Serialize)]
+pub struct MonteCarloSummary {
+    pub length: i32,
+    pub paths: usize,
+    pub kf_wins: usize,
+    pub p5: f64,
+    pub p50: f64,
+    pub p95: f64,
+}
+
+/// Builds `paths` synthetic histories per holding length by drawing years
+/// from `range` with replacement. A drawn year contributes both its index
+/// return and its avkastningsskatt, so returns and tax stay paired.
+/// `on_path` is called after each simulated path, e.g. to show progress.
+/// Fails, naming the year, when a year in `range` has no record.
+pub fn monte_carlo(
+    records: &BTreeMap<i32, Record>,
+    lengths: &[i32],
+    range: (i32, i32),
+    params: &SimulationParams,
+    paths: usize,
+    rng: &mut impl Rng,
+    mut on_path: impl FnMut(),
+) -> Result<Vec<MonteCarloSummary>, String> {
+    let steps: Vec<YearStep> = YearStep::of_range(records, range)?.into_values().collect();
+
+    Ok(lengths
+        .iter()
+        .map(|&length| {
+            let advantages: Vec<f64> = (0..paths)
+                .map(|_| {
+                    let mut accounts = Accounts::new(params);
+                    for _ in 0..length {
+                        accounts.step(steps[rng.random_range(0..steps.len())], params);
+                    }
+                    on_path();
+                    accounts.kapitalförsäkring() - accounts.aktiekonto(params)
+                })
+                .collect();
+
+            MonteCarloSummary {
+                length,
+                paths,
+                kf_wins: advantages.iter().filter(|&&a| a > 0.0).count(),
+                p5: percentile(&advantages, 5.0).unwrap_or(f64::NAN),
+                p50: percentile(&advantages, 50.0).unwrap_or(f64::NAN),
+                p95: percentile(&advantages, 95.0).unwrap_or(f64::NAN),
+            }
+        })
+        .collect())
+}
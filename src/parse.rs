@@ -0,0 +1,353 @@
+use chrono::NaiveDate;
+use serde::Deserialize;
+use std::fmt;
+use std::str::FromStr;
+
+/// A value that [`parse_swedish_number`] could not read as a number.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidNumber {
+    pub input: String,
+    pub reason: &'static str,
+}
+
+impl fmt::Display for InvalidNumber {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid number {:?}: {}", self.input, self.reason)
+    }
+}
+
+impl std::error::Error for InvalidNumber {}
+
+/// Why an input line was rejected. Every variant carries the offending line.
+#[derive(Debug)]
+pub enum ParseError {
+    /// The line has fewer columns than needed; `column` is 1-based.
+    MissingColumn {
+        line: String,
+        column: usize,
+    },
+    BadDate {
+        line: String,
+        source: chrono::ParseError,
+    },
+    BadNumber {
+        line: String,
+        source: InvalidNumber,
+    },
+}
+
+impl ParseError {
+    pub fn line(&self) -> &str {
+        match self {
+            ParseError::MissingColumn { line, .. }
+            | ParseError::BadDate { line, .. }
+            | ParseError::BadNumber { line, .. } => line,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::MissingColumn { line, column } => {
+                write!(f, "missing column {column} in {line:?}")
+            }
+            ParseError::BadDate { line, source } => write!(f, "bad date in {line:?}: {source}"),
+            ParseError::BadNumber { line, source } => write!(f, "{source} in {line:?}"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ParseError::MissingColumn { .. } => None,
+            ParseError::BadDate { source, .. } => Some(source),
+            ParseError::BadNumber { source, .. } => Some(source),
+        }
+    }
+}
+
+/// Splits `line` on `delimiter`, except inside double-quoted fields.
+fn split_fields(line: &str, delimiter: char) -> Vec<&str> {
+    let mut fields = Vec::new();
+    let mut start = 0;
+    let mut quoted = false;
+    for (i, c) in line.char_indices() {
+        if c == '"' {
+            quoted = !quoted;
+        } else if c == delimiter && !quoted {
+            fields.push(&line[start..i]);
+            start = i + c.len_utf8();
+        }
+    }
+    fields.push(&line[start..]);
+    fields
+}
+
+/// The field at `index`, with surrounding whitespace (such as the `\r` of a
+/// CRLF line ending) and a pair of surrounding double quotes removed.
+fn column<'a>(parts: &[&'a str], line: &str, index: usize) -> Result<&'a str, ParseError> {
+    parts
+        .get(index)
+        .map(|part| {
+            let part = part.trim();
+            part.strip_prefix('"')
+                .and_then(|p| p.strip_suffix('"'))
+                .map_or(part, str::trim)
+        })
+        .ok_or_else(|| ParseError::MissingColumn {
+            line: line.to_string(),
+            column: index + 1,
+        })
+}
+
+fn parse_date(s: &str, line: &str) -> Result<NaiveDate, ParseError> {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").map_err(|source| ParseError::BadDate {
+        line: line.to_string(),
+        source,
+    })
+}
+
+fn parse_value(s: &str, line: &str, locale: Option<NumberLocale>) -> Result<f64, ParseError> {
+    parse_number(s, locale).map_err(|source| ParseError::BadNumber {
+        line: line.to_string(),
+        source,
+    })
+}
+
+/// Whether `s` is an integer written with `sep` between groups of three
+/// digits, e.g. "1,234" or "12,345,678".
+fn is_grouped(s: &str, sep: char) -> bool {
+    let digits = s.strip_prefix('-').unwrap_or(s);
+    let mut groups = digits.split(sep);
+    let first = groups.next().unwrap_or_default();
+    let mut count = 1;
+    let rest_ok = groups.all(|g| {
+        count += 1;
+        g.len() == 3 && g.bytes().all(|b| b.is_ascii_digit())
+    });
+    count > 1
+        && (1..=3).contains(&first.len())
+        && first.bytes().all(|b| b.is_ascii_digit())
+        && rest_ok
+}
+
+/// Splits scientific notation such as "2,5e2" into the mantissa and the
+/// exponent, so that the separators are only looked for in the mantissa.
+fn split_exponent(s: &str) -> (&str, Option<&str>) {
+    match s.rfind(['e', 'E']) {
+        Some(i) if i > 0 => {
+            let exponent = &s[i + 1..];
+            let digits = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+            if !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) {
+                return (&s[..i], Some(exponent));
+            }
+            (s, None)
+        }
+        _ => (s, None),
+    }
+}
+
+/// `mantissa` with the exponent split off by [`split_exponent`] put back.
+fn with_exponent(mantissa: String, exponent: Option<&str>) -> String {
+    match exponent {
+        Some(exponent) => format!("{mantissa}e{exponent}"),
+        None => mantissa,
+    }
+}
+
+/// Reads `normalized`, the cleaned-up form of `input`, as a finite number.
+/// `f64::from_str` also takes "NaN" and "inf", which are not data values.
+fn to_finite(input: &str, normalized: &str) -> Result<f64, InvalidNumber> {
+    let invalid = |reason| InvalidNumber {
+        input: input.to_string(),
+        reason,
+    };
+    let value = f64::from_str(normalized).map_err(|_| invalid("not a number"))?;
+    if !value.is_finite() {
+        return Err(invalid("not a finite number"));
+    }
+    Ok(value)
+}
+
+/// Parses a number in either Swedish ("1 234,56") or English ("1,234.56",
+/// "1234.56") notation. When both separators are present the last one is the
+/// decimal separator. A lone comma is read as a decimal comma unless it is
+/// followed by exactly three digits, so "1,234" is a thousand and "2,45" is
+/// two and a bit. In scientific notation ("2,5e2") a lone separator is
+/// always the decimal one.
+pub fn parse_swedish_number(s: &str) -> Result<f64, InvalidNumber> {
+    let compact: String = s
+        .trim()
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '\u{a0}'))
+        .collect();
+    let (compact, exponent) = split_exponent(&compact);
+    let grouped = |sep| exponent.is_none() && is_grouped(compact, sep);
+
+    let (decimal, group) = match (compact.rfind(','), compact.rfind('.')) {
+        (Some(comma), Some(dot)) if comma > dot => (Some(','), Some('.')),
+        (Some(_), Some(_)) => (Some('.'), Some(',')),
+        (Some(_), None) if grouped(',') => (None, Some(',')),
+        (Some(_), None) => (Some(','), None),
+        (None, Some(_)) if grouped('.') => (None, Some('.')),
+        (None, Some(_)) => (Some('.'), None),
+        (None, None) => (None, None),
+    };
+
+    let (int_part, frac_part) = match decimal.and_then(|d| compact.rsplit_once(d)) {
+        Some((int_part, frac_part)) => (int_part, Some(frac_part)),
+        None => (compact, None),
+    };
+
+    let int_clean = match group {
+        Some(g) if int_part.contains(g) => {
+            if !is_grouped(int_part, g) {
+                return Err(InvalidNumber {
+                    input: s.to_string(),
+                    reason: "malformed digit grouping",
+                });
+            }
+            int_part.replace(g, "")
+        }
+        _ => int_part.to_string(),
+    };
+
+    let normalized = match frac_part {
+        Some(frac) => format!("{int_clean}.{frac}"),
+        None => int_clean,
+    };
+    to_finite(s, &with_exponent(normalized, exponent))
+}
+
+/// How the numbers of an input file are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum NumberLocale {
+    /// "1 234,56": a decimal comma and digits grouped with spaces
+    #[default]
+    Sv,
+    /// "1,234.56": a decimal point and digits grouped with commas
+    En,
+}
+
+/// Parses a number written as in `locale`, or in either notation as
+/// [`parse_swedish_number`] guesses it when `None`. With a locale, "1,234"
+/// is one and a bit in `sv` and a thousand in `en`, and a number using the
+/// other locale's decimal separator is an error.
+pub fn parse_number(s: &str, locale: Option<NumberLocale>) -> Result<f64, InvalidNumber> {
+    let Some(locale) = locale else {
+        return parse_swedish_number(s);
+    };
+    let invalid = |reason| InvalidNumber {
+        input: s.to_string(),
+        reason,
+    };
+    let compact: String = s.trim().chars().filter(|&c| c != '\u{a0}').collect();
+    let (compact, exponent) = split_exponent(&compact);
+
+    let normalized = match locale {
+        NumberLocale::Sv => {
+            if compact.contains('.') {
+                return Err(invalid("a point is not a decimal separator in sv numbers"));
+            }
+            let compact = compact.replace(' ', "");
+            let (int_part, frac_part) = compact.split_once(',').unwrap_or((&compact, ""));
+            if frac_part.contains(',') {
+                return Err(invalid("more than one decimal comma"));
+            }
+            format!("{int_part}.{frac_part}")
+        }
+        NumberLocale::En => {
+            if compact.contains(' ') {
+                return Err(invalid("a space is not a digit separator in en numbers"));
+            }
+            let (int_part, frac_part) = compact.split_once('.').unwrap_or((compact, ""));
+            if frac_part.contains(',') {
+                return Err(invalid("a comma is not a decimal separator in en numbers"));
+            }
+            let int_clean = if int_part.contains(',') {
+                if !is_grouped(int_part, ',') {
+                    return Err(invalid("malformed digit grouping"));
+                }
+                int_part.replace(',', "")
+            } else {
+                int_part.to_string()
+            };
+            format!("{int_clean}.{frac_part}")
+        }
+    };
+    to_finite(s, &with_exponent(normalized, exponent))
+}
+
+pub struct RecordOmxs30 {
+    pub date: NaiveDate,
+    pub value: f64,
+}
+
+/// Parses a tab-separated line of the OMXS30 file, with the numbers
+/// written as in `locale` (see [`parse_number`]).
+pub fn parse_omxs30_line(
+    line: &str,
+    locale: Option<NumberLocale>,
+) -> Result<RecordOmxs30, ParseError> {
+    let parts: Vec<&str> = line.split('\t').collect();
+
+    let date = parse_date(column(&parts, line, 0)?, line)?;
+    let value = parse_value(column(&parts, line, 1)?, line, locale)?;
+
+    Ok(RecordOmxs30 { date, value })
+}
+
+pub struct RecordSLR {
+    pub date: NaiveDate,
+    pub value: f64,
+}
+
+/// Column (1-based) of the SLR file holding "Räntesats %", the rate
+/// observed on each date. Column 1 is the date and column 3 "Medelvärde
+/// hittills i år", the average of the observations so far that year.
+pub const SLR_RATE_COLUMN: usize = 2;
+
+/// Field delimiter of the SLR export, as usual for Swedish CSV files.
+pub const SLR_DELIMITER: char = ';';
+
+/// Parses a line of the SLR export, reading the value from `value_column`
+/// (1-based, see [`SLR_RATE_COLUMN`]). Fields are split on `delimiter` and
+/// may be enclosed in double quotes.
+pub fn parse_slr_line(
+    line: &str,
+    value_column: usize,
+    delimiter: char,
+    locale: Option<NumberLocale>,
+) -> Result<RecordSLR, ParseError> {
+    let parts = split_fields(line, delimiter);
+
+    let date = parse_date(column(&parts, line, 0)?, line)?;
+    // Column 0 does not exist, so it is missing from every line.
+    let value_index = value_column
+        .checked_sub(1)
+        .ok_or_else(|| ParseError::MissingColumn {
+            line: line.to_string(),
+            column: 0,
+        })?;
+    let value = parse_value(column(&parts, line, value_index)?, line, locale)?;
+
+    Ok(RecordSLR { date, value })
+}
+
+pub struct RecordCpi {
+    pub date: NaiveDate,
+    pub value: f64,
+}
+
+pub fn parse_cpi_line(line: &str, locale: Option<NumberLocale>) -> Result<RecordCpi, ParseError> {
+    let parts = split_fields(line, ';');
+
+    let date = parse_date(column(&parts, line, 0)?, line)?;
+    let value = parse_value(column(&parts, line, 1)?, line, locale)?;
+
+    Ok(RecordCpi { date, value })
+}
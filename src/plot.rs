@@ -0,0 +1,60 @@
+use crate::simulate::SeriesEntry;
+use plotters::prelude::*;
+use std::path::Path;
+
+/// Draws the final aktiekonto and kapitalförsäkring multipliers of one
+/// holding length against start year as an SVG line chart at `path`.
+pub fn plot_series(
+    path: &Path,
+    length: i32,
+    series: &[SeriesEntry],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (Some(first), Some(last)) = (series.first(), series.last()) else {
+        return Err(format!("no {length} year series to plot").into());
+    };
+    let max_value = series
+        .iter()
+        .map(|e| e.aktiekonto.max(e.kapitalförsäkring))
+        .fold(0.0, f64::max);
+
+    let root = SVGBackend::new(path, (1024, 640)).into_drawing_area();
+    root.fill(&WHITE)?;
+    let mut chart = ChartBuilder::on(&root)
+        .caption(format!("{length} years"), ("sans-serif", 24))
+        .margin(16)
+        .x_label_area_size(40)
+        .y_label_area_size(56)
+        .build_cartesian_2d(
+            first.start_year..last.start_year.max(first.start_year + 1),
+            0.0..max_value * 1.05,
+        )?;
+    chart
+        .configure_mesh()
+        .x_desc("Start year")
+        .y_desc("Final value")
+        .draw()?;
+
+    for (label, color, value) in [
+        (
+            "AK",
+            BLUE,
+            (|e: &SeriesEntry| e.aktiekonto) as fn(&SeriesEntry) -> f64,
+        ),
+        ("KF", RED, |e: &SeriesEntry| e.kapitalförsäkring),
+    ] {
+        chart
+            .draw_series(LineSeries::new(
+                series.iter().map(|e| (e.start_year, value(e))),
+                color,
+            ))?
+            .label(label)
+            .legend(move |(x, y)| PathElement::new([(x, y), (x + 20, y)], color));
+    }
+    chart
+        .configure_series_labels()
+        .background_style(WHITE)
+        .border_style(BLACK)
+        .draw()?;
+    root.present()?;
+    Ok(())
+}
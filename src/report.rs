@@ -0,0 +1,1302 @@
+use crate::analysis::{find_breakeven, LengthAverages, RegimeGroup, RegimeWindow};
+use crate::monte_carlo::MonteCarloSummary;
+use crate::results::Results;
+use crate::simulate::{Record, SeriesEntry, SimulationParams};
+use crate::stats::{histogram, mean};
+use crate::summary::{ColumnSummary, SeriesSummary};
+use crate::switch::{mean_switch_after, SwitchEntry};
+use crate::tax::{TaxExplanation, TaxParams};
+use crate::trajectory::TrajectoryYear;
+use crate::withdraw::WithdrawalEntry;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+/// The simulated results of one index.
+pub type IndexSeries = (String, Results);
+
+/// The combined yearly records of one index, as used by the simulation.
+pub type IndexRecords = (String, BTreeMap<i32, Record>);
+
+/// The averages of every holding length of one index, as swept by
+/// `--find-breakeven`.
+pub type IndexAverages = (String, Vec<LengthAverages>);
+
+/// What the start years of each holding length are ordered by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum SortKey {
+    /// Start year
+    #[default]
+    Year,
+    /// Aktiekonto final value
+    Ak,
+    /// Kapitalförsäkring final value
+    Kf,
+    /// KF minus AK percentage difference
+    Diff,
+}
+
+/// Orders `entries` by `key`, ascending unless `descending`. Entries that
+/// tie keep their order.
+pub fn sort_entries(entries: &mut [SeriesEntry], key: SortKey, descending: bool) {
+    let value = |e: &SeriesEntry| match key {
+        SortKey::Year => e.start_year as f64,
+        SortKey::Ak => e.aktiekonto,
+        SortKey::Kf => e.kapitalförsäkring,
+        SortKey::Diff => e.kf_difference_pct(),
+    };
+    entries.sort_by(|a, b| {
+        let order = value(a).total_cmp(&value(b));
+        if descending {
+            order.reverse()
+        } else {
+            order
+        }
+    });
+}
+
+/// Fractional digits of printed values when nothing else is asked for.
+pub const DEFAULT_PRECISION: usize = 2;
+
+/// How the final values of [`write_text`], [`write_winner`],
+/// [`write_switches`] and [`write_markdown`] are rendered. The default writes
+/// plain numbers, as suits multipliers.
+#[derive(Debug, Clone, Default)]
+pub struct Amounts {
+    /// Appended after each value, e.g. "SEK".
+    pub currency_label: Option<String>,
+    /// Separate groups of three integer digits with a space, as in Swedish.
+    pub group_thousands: bool,
+    /// Least width of each numeric column of the start-year tables, which
+    /// are otherwise as wide as their widest value.
+    pub table_width: usize,
+}
+
+impl Amounts {
+    pub fn format(&self, value: f64, precision: usize) -> String {
+        self.render(format!("{value:.precision$}"))
+    }
+
+    /// Like [`Amounts::format`], with a `+` before positive values.
+    pub fn format_signed(&self, value: f64, precision: usize) -> String {
+        self.render(format!("{value:+.precision$}"))
+    }
+
+    fn format_stat(&self, value: Option<f64>, precision: usize) -> String {
+        value.map_or_else(|| "-".to_string(), |v| self.format(v, precision))
+    }
+
+    fn render(&self, number: String) -> String {
+        let number = if self.group_thousands {
+            let digits_start = number.find(|c: char| c.is_ascii_digit()).unwrap_or(0);
+            let digits_end = number.find('.').unwrap_or(number.len());
+            let (sign, rest) = number.split_at(digits_start);
+            let (digits, fraction) = rest.split_at(digits_end - digits_start);
+            let mut grouped = String::new();
+            for (i, digit) in digits.chars().enumerate() {
+                if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                    grouped.push(' ');
+                }
+                grouped.push(digit);
+            }
+            format!("{sign}{grouped}{fraction}")
+        } else {
+            number
+        };
+        match &self.currency_label {
+            Some(label) => format!("{number} {label}"),
+            None => number,
+        }
+    }
+}
+
+/// The cells of one start year in [`write_series`]: the year, the final
+/// values, the KF vs AK difference, then the CAGR, drawdown, KF tax and,
+/// with contributions, IRR columns after their labels.
+fn series_row(e: &SeriesEntry, len: i32, p: usize, amounts: &Amounts) -> Vec<(String, bool)> {
+    // The drag-only account and the benchmark, if any, follow the ISK.
+    let values: Vec<f64> = [e.aktiekonto, e.kapitalförsäkring, e.isk]
+        .into_iter()
+        .chain(e.drag_only)
+        .chain(e.benchmark)
+        .collect();
+    let drawdowns = [
+        e.aktiekonto_max_drawdown,
+        e.kapitalförsäkring_max_drawdown,
+        e.isk_max_drawdown,
+    ]
+    .into_iter()
+    .chain(e.drag_only_max_drawdown)
+    .chain(e.benchmark_max_drawdown);
+    let label = |text: &str| (text.to_string(), false);
+    let number = |text: String| (text, true);
+
+    let mut row = vec![label(&format!("{}:", e.start_year))];
+    row.extend(values.iter().map(|&v| number(amounts.format(v, p))));
+    row.push(number(format!("{:+.p$}%", e.kf_difference_pct())));
+    row.push(label("CAGR"));
+    row.extend(
+        values
+            .iter()
+            .map(|&v| number(format_percent(e.cagr(v, len).map(|c| c * 100.0), p))),
+    );
+    row.push(label("max drawdown"));
+    row.extend(drawdowns.map(|d| number(format!("{:.p$}%", d * 100.0))));
+    row.push(label("KF tax"));
+    row.push(number(amounts.format(e.kapitalförsäkring_tax, p)));
+    let irrs = [e.aktiekonto_irr, e.kapitalförsäkring_irr, e.isk_irr];
+    if irrs.iter().any(Option::is_some) {
+        row.push(label("IRR"));
+        row.extend(irrs.map(|irr| number(format_stat(irr.map(|v| v * 100.0), p) + "%")));
+    }
+    row
+}
+
+/// A percentage, or `-` without a percent sign when there is none.
+fn format_percent(value: Option<f64>, precision: usize) -> String {
+    value.map_or_else(|| "-".to_string(), |v| format!("{v:.precision$}%"))
+}
+
+fn format_stat(value: Option<f64>, precision: usize) -> String {
+    value.map_or_else(|| "-".to_string(), |v| format!("{v:.precision$}"))
+}
+
+/// Writes the text output for every length in `lengths`, with `precision`
+/// fractional digits, and an overview of them all when there is more than
+/// one.
+pub fn write_text(
+    out: &mut impl Write,
+    lengths: &[i32],
+    results: &Results,
+    precision: usize,
+    amounts: &Amounts,
+) -> io::Result<()> {
+    for &length in lengths {
+        let entries = results.entries(length);
+        write_series(
+            out,
+            length,
+            entries,
+            &results.summaries[&length],
+            precision,
+            amounts,
+        )?;
+    }
+    if lengths.len() > 1 {
+        write_overview(out, lengths, results, precision, amounts)?;
+    }
+    Ok(())
+}
+
+fn format_pct(value: Option<f64>, precision: usize, sign: bool) -> String {
+    match value {
+        Some(v) if sign => format!("{v:+.precision$}%"),
+        Some(v) => format!("{v:.precision$}%"),
+        None => "-".to_string(),
+    }
+}
+
+/// Writes one line per holding length with the average AK and KF final
+/// values, the share of start years KF won and its average percentage lead.
+pub fn write_overview(
+    out: &mut impl Write,
+    lengths: &[i32],
+    results: &Results,
+    precision: usize,
+    amounts: &Amounts,
+) -> io::Result<()> {
+    let p = precision;
+    writeln!(out, "\nAll lengths:    AK    KF    KF wins    KF vs AK")?;
+    for &length in lengths {
+        let summary = &results.summaries[&length];
+        writeln!(
+            out,
+            "{length} years:    {}    {}    {}    {}",
+            amounts.format_stat(summary.aktiekonto.mean, p),
+            amounts.format_stat(summary.kapitalförsäkring.mean, p),
+            format_pct(summary.kf_win_rate.map(|r| r * 100.0), p, false),
+            format_pct(summary.mean_kf_difference, p, true)
+        )?;
+    }
+    Ok(())
+}
+
+/// Writes every start year of one holding length followed by its summary
+/// statistics, or a single line when it has no start years.
+pub fn write_series(
+    out: &mut impl Write,
+    len: i32,
+    series: &[SeriesEntry],
+    summary: &SeriesSummary,
+    precision: usize,
+    amounts: &Amounts,
+) -> io::Result<()> {
+    let p = precision;
+    if series.is_empty() {
+        return writeln!(out, "\n{len} years: no start years");
+    }
+    writeln!(out, "\n{len} years:")?;
+    // Each row is a list of cells, `true` for the numeric ones that are
+    // right-aligned in columns.
+    let rows: Vec<Vec<(String, bool)>> = series
+        .iter()
+        .map(|e| series_row(e, len, p, amounts))
+        .collect();
+    let mut widths: Vec<usize> = Vec::new();
+    for row in &rows {
+        for (i, (cell, numeric)) in row.iter().enumerate() {
+            let mut width = cell.chars().count();
+            if *numeric {
+                width = width.max(amounts.table_width);
+            }
+            match widths.get_mut(i) {
+                Some(w) => *w = (*w).max(width),
+                None => widths.push(width),
+            }
+        }
+    }
+    for row in &rows {
+        let mut line = String::new();
+        for (i, ((cell, numeric), &width)) in row.iter().zip(&widths).enumerate() {
+            // A label is followed by its values with a single space.
+            line += match i {
+                0 => "",
+                1 => "     ",
+                _ if !row[i - 1].1 && i > 1 => " ",
+                _ => "    ",
+            };
+            if *numeric {
+                line += &format!("{cell:>width$}");
+            } else {
+                line += &format!("{cell:<width$}");
+            }
+        }
+        writeln!(out, "{line}")?;
+    }
+
+    // One value per account type, in the order AK, KF, ISK, drag-only and
+    // benchmark.
+    let columns = |stat: fn(&ColumnSummary) -> Option<f64>, suffix: &str| {
+        [
+            &summary.aktiekonto,
+            &summary.kapitalförsäkring,
+            &summary.isk,
+        ]
+        .into_iter()
+        .chain(&summary.drag_only)
+        .chain(&summary.benchmark)
+        .map(|column| format_stat(stat(column), p) + suffix)
+        .collect::<Vec<_>>()
+        .join("    ")
+    };
+    let percent_columns = |stat: fn(&ColumnSummary) -> Option<f64>| {
+        [
+            &summary.aktiekonto,
+            &summary.kapitalförsäkring,
+            &summary.isk,
+        ]
+        .into_iter()
+        .chain(&summary.drag_only)
+        .chain(&summary.benchmark)
+        .map(|column| format_percent(stat(column), p))
+        .collect::<Vec<_>>()
+        .join("    ")
+    };
+    let amount_columns = |stat: fn(&ColumnSummary) -> Option<f64>| {
+        [
+            &summary.aktiekonto,
+            &summary.kapitalförsäkring,
+            &summary.isk,
+        ]
+        .into_iter()
+        .chain(&summary.drag_only)
+        .chain(&summary.benchmark)
+        .map(|column| amounts.format_stat(stat(column), p))
+        .collect::<Vec<_>>()
+        .join("    ")
+    };
+    write!(
+        out,
+        "{len} years averages:    {}    {}%",
+        amount_columns(|c| c.mean),
+        summary
+            .mean_kf_difference
+            .map_or_else(|| "-".to_string(), |d| format!("{d:+.p$}"))
+    )?;
+    if let Some(interval) = summary.kf_difference_interval {
+        write!(
+            out,
+            "    ({:.0}% CI {:+.p$}% to {:+.p$}%)",
+            interval.level, interval.lower, interval.upper
+        )?;
+    }
+    writeln!(out)?;
+    writeln!(
+        out,
+        "{len} years geometric means:    {}",
+        amount_columns(|c| c.geometric_mean)
+    )?;
+    writeln!(
+        out,
+        "{len} years medians:    {}",
+        amount_columns(|c| c.median)
+    )?;
+    writeln!(
+        out,
+        "{len} years std devs:    {}",
+        amount_columns(|c| c.std_dev)
+    )?;
+    writeln!(
+        out,
+        "{len} years average CAGR:    {}",
+        percent_columns(|c| c.mean_cagr.map(|v| v * 100.0))
+    )?;
+    writeln!(
+        out,
+        "{len} years average max drawdown:    {}",
+        columns(|c| c.mean_max_drawdown.map(|v| v * 100.0), "%")
+    )?;
+    writeln!(
+        out,
+        "{len} years average Sharpe ratio:    {}",
+        columns(|c| c.mean_sharpe, "")
+    )?;
+    writeln!(
+        out,
+        "{len} years average volatility:    {}",
+        columns(|c| c.mean_volatility.map(|v| v * 100.0), "%")
+    )?;
+    writeln!(
+        out,
+        "{len} years average KF tax:    {}",
+        amounts.format_stat(summary.mean_kapitalförsäkring_tax, p)
+    )?;
+    if summary.aktiekonto.mean_irr.is_some() {
+        writeln!(
+            out,
+            "{len} years average IRR:    {}",
+            columns(|c| c.mean_irr.map(|v| v * 100.0), "%")
+        )?;
+    }
+
+    let total = summary.count;
+    for (name, wins, advantage) in [
+        ("KF", summary.kf_wins, summary.kf_average_advantage),
+        ("AK", summary.ak_wins, summary.ak_average_advantage),
+    ] {
+        write!(
+            out,
+            "{name} wins {wins}/{total} ({:.p$}%)",
+            wins as f64 / total as f64 * 100.0
+        )?;
+        if let Some(average) = advantage {
+            write!(out, ", by {average:.p$}% on average")?;
+        }
+        writeln!(out)?;
+    }
+    for (label, outcome) in [
+        ("Best", summary.best_for_kf),
+        ("Worst", summary.worst_for_kf),
+    ] {
+        if let Some(o) = outcome {
+            writeln!(
+                out,
+                "{label} start year for KF: {}, KF {} vs AK {} ({})",
+                o.start_year,
+                amounts.format(o.kapitalförsäkring, p),
+                amounts.format(o.aktiekonto, p),
+                amounts.format_signed(o.kf_advantage(), p)
+            )?;
+        }
+    }
+    if let Some(trend) = summary.kf_advantage_trend {
+        writeln!(
+            out,
+            "KF advantage trend: {} per start year (R² {:.p$})",
+            amounts.format_signed(trend.slope, p),
+            trend.r_squared
+        )?;
+    }
+    Ok(())
+}
+
+/// Writes how much more the aktiekonto ends with on average when its gains
+/// are only taxed on the final sale (`deferred`) than when they are taxed
+/// every year (`annual`), in kronor and percent.
+pub fn write_deferral(
+    out: &mut impl Write,
+    lengths: &[i32],
+    annual: &BTreeMap<i32, Vec<SeriesEntry>>,
+    deferred: &BTreeMap<i32, Vec<SeriesEntry>>,
+    precision: usize,
+) -> io::Result<()> {
+    let p = precision;
+    writeln!(
+        out,
+        "\nValue of deferring the AK tax to the end, on average:"
+    )?;
+    for length in lengths {
+        // Paired by start year, so the order of either series does not matter.
+        let deferred_by_year: BTreeMap<i32, f64> = deferred
+            .get(length)
+            .into_iter()
+            .flatten()
+            .map(|d| (d.start_year, d.aktiekonto))
+            .collect();
+        let pairs: Vec<(f64, f64)> = annual
+            .get(length)
+            .into_iter()
+            .flatten()
+            .filter_map(|a| Some((a.aktiekonto, *deferred_by_year.get(&a.start_year)?)))
+            .collect();
+        let differences: Vec<f64> = pairs.iter().map(|(a, d)| d - a).collect();
+        let percentages: Vec<f64> = pairs.iter().map(|(a, d)| (d / a - 1.0) * 100.0).collect();
+        match (mean(&differences), mean(&percentages)) {
+            (Some(difference), Some(percentage)) => writeln!(
+                out,
+                "{length} years:    {difference:+.p$}    {percentage:+.p$}%"
+            )?,
+            _ => writeln!(out, "{length} years:    -")?,
+        }
+    }
+    Ok(())
+}
+
+/// Writes one line naming the account type with the highest average final
+/// value for the holding length, and its lead over the runner-up.
+pub fn write_winner(
+    out: &mut impl Write,
+    len: i32,
+    summary: &SeriesSummary,
+    precision: usize,
+    amounts: &Amounts,
+) -> io::Result<()> {
+    let p = precision;
+    let mut averages: Vec<(&str, f64)> = [
+        ("AK", summary.aktiekonto.mean),
+        ("KF", summary.kapitalförsäkring.mean),
+        ("ISK", summary.isk.mean),
+    ]
+    .into_iter()
+    .filter_map(|(name, mean)| Some((name, mean?)))
+    .collect();
+    averages.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    match averages[..] {
+        [(winner, best), (runner_up, second), ..] => writeln!(
+            out,
+            "{len} years: {winner} has the highest average final value, {}, {:.p$}% above {runner_up}",
+            amounts.format(best, p),
+            (best / second - 1.0) * 100.0
+        ),
+        _ => writeln!(out, "{len} years: no start years"),
+    }
+}
+
+pub fn write_index_header(out: &mut impl Write, name: &str) -> io::Result<()> {
+    writeln!(out, "\n=== {name} ===")
+}
+
+pub fn write_monte_carlo(
+    out: &mut impl Write,
+    result: &MonteCarloSummary,
+    precision: usize,
+) -> io::Result<()> {
+    let p = precision;
+    writeln!(out, "\n{} years:", result.length)?;
+    writeln!(
+        out,
+        "KF - AK    p5: {:.p$}    p50: {:.p$}    p95: {:.p$}",
+        result.p5, result.p50, result.p95
+    )?;
+    writeln!(
+        out,
+        "KF wins {}/{} ({:.p$}%)",
+        result.kf_wins,
+        result.paths,
+        result.kf_wins as f64 / result.paths as f64 * 100.0
+    )
+}
+
+/// Writes how many years each account kept paying out after `len` years of
+/// accumulation, with a `+` on counts cut short by the end of the data.
+pub fn write_withdrawals(
+    out: &mut impl Write,
+    len: i32,
+    rate: f64,
+    entries: &[WithdrawalEntry],
+    precision: usize,
+) -> io::Result<()> {
+    let p = precision;
+    writeln!(out, "\n{len} years, then withdrawing {rate:.p$}% a year:")?;
+    let lasted = |years: Option<i32>, available: i32| match years {
+        Some(years) => format!("{years} years"),
+        None => format!("{available}+ years"),
+    };
+    for e in entries {
+        writeln!(
+            out,
+            "{}:     AK {}    KF {}    ISK {}",
+            e.start_year,
+            lasted(e.aktiekonto_years, e.years_available),
+            lasted(e.kapitalförsäkring_years, e.years_available),
+            lasted(e.isk_years, e.years_available)
+        )?;
+    }
+    if entries.is_empty() {
+        return writeln!(out, "no start years leave room for withdrawals");
+    }
+    let total = entries.len();
+    let funded = |years: fn(&WithdrawalEntry) -> Option<i32>| {
+        entries.iter().filter(|&e| years(e).is_none()).count()
+    };
+    writeln!(
+        out,
+        "Still paying at the end of the data: AK {}/{total}    KF {}/{total}    ISK {}/{total}",
+        funded(|e| e.aktiekonto_years),
+        funded(|e| e.kapitalförsäkring_years),
+        funded(|e| e.isk_years)
+    )
+}
+
+/// Writes the average KF vs AK difference of the holding periods before,
+/// across and after the tax change of `change_year`, one line per length,
+/// with the number of start years of each group.
+pub fn write_regime_split(
+    out: &mut impl Write,
+    change_year: i32,
+    groups: &[RegimeGroup],
+    precision: usize,
+) -> io::Result<()> {
+    let p = precision;
+    writeln!(
+        out,
+        "\nKF vs AK by holding periods before, straddling and after {change_year}:"
+    )?;
+    let mut lengths: Vec<i32> = groups.iter().map(|g| g.length).collect();
+    lengths.dedup();
+    for length in lengths {
+        write!(out, "{length} years:")?;
+        for group in groups.iter().filter(|g| g.length == length) {
+            let label = match group.window {
+                RegimeWindow::Before => "before",
+                RegimeWindow::Straddling => "straddling",
+                RegimeWindow::After => "after",
+            };
+            write!(
+                out,
+                "    {label} {} ({} start {})",
+                format_pct(group.mean_kf_difference, p, true),
+                group.count,
+                if group.count == 1 { "year" } else { "years" }
+            )?;
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+/// Writes the avkastningsskatt calculation of one year step by step, with
+/// the values substituted into the formula.
+pub fn write_tax_explanation(
+    out: &mut impl Write,
+    explanation: &TaxExplanation,
+    precision: usize,
+) -> io::Result<()> {
+    let p = precision;
+    let e = explanation;
+    let source = match &e.period {
+        Some(period) => match period.to_year {
+            Some(to) => format!("--tax-schedule period {}..={to}", period.from_year),
+            None => format!("--tax-schedule period from {}", period.from_year),
+        },
+        None => "the built-in rule".to_string(),
+    };
+    writeln!(out, "Avkastningsskatt for {}, from {source}:", e.year)?;
+    writeln!(out, "SLR:                {:.p$}%", e.slr)?;
+    writeln!(
+        out,
+        "supplement:         {:+.p$} percentage points",
+        e.supplement
+    )?;
+    writeln!(out, "SLR + supplement:   {:.p$}%", e.slr + e.supplement)?;
+    if e.floor.is_finite() {
+        writeln!(
+            out,
+            "floor:              {:.p$}%, {}",
+            e.floor,
+            if e.floor_applied {
+                "above the SLR + supplement, so it applies"
+            } else {
+                "not above the SLR + supplement"
+            }
+        )?;
+    } else {
+        writeln!(out, "floor:              none")?;
+    }
+    if e.floor.is_finite() {
+        writeln!(
+            out,
+            "schablonintäkt:     max({:.p$}% + {:.p$}, {:.p$}%) = {:.p$}%",
+            e.slr, e.supplement, e.floor, e.schablonintäkt
+        )?;
+    } else {
+        writeln!(
+            out,
+            "schablonintäkt:     {:.p$}% + {:.p$} = {:.p$}%",
+            e.slr, e.supplement, e.schablonintäkt
+        )?;
+    }
+    writeln!(out, "tax rate:           {:.p$}%", e.base_rate * 100.0)?;
+    // The result has more digits than the inputs, so it gets two more.
+    let q = p + 2;
+    writeln!(
+        out,
+        "avkastningsskatt:   {:.p$}% × {:.p$}% = {:.q$}% of the kapitalunderlag",
+        e.schablonintäkt,
+        e.base_rate * 100.0,
+        e.rate * 100.0
+    )
+}
+
+/// Writes the best year to switch from AK to KF for each start year of one
+/// holding length, with the final values of never switching for comparison.
+pub fn write_switches(
+    out: &mut impl Write,
+    len: i32,
+    entries: &[SwitchEntry],
+    precision: usize,
+    amounts: &Amounts,
+) -> io::Result<()> {
+    let p = precision;
+    writeln!(out, "\n{len} years, switching from AK to KF:")?;
+    for e in entries {
+        writeln!(
+            out,
+            "{}:     switch after {} years    {}    (AK only {}    KF only {})",
+            e.start_year,
+            e.switch_after,
+            amounts.format(e.value, p),
+            amounts.format(e.aktiekonto, p),
+            amounts.format(e.kapitalförsäkring, p)
+        )?;
+    }
+    writeln!(
+        out,
+        "Average best switch: after {} years",
+        format_stat(mean_switch_after(entries), p)
+    )
+}
+
+/// Writes the balances of one start year at the end of every year, with
+/// the return ratio applied and the tax taken during the year.
+pub fn write_trajectory(
+    out: &mut impl Write,
+    start_year: i32,
+    years: &[TrajectoryYear],
+    precision: usize,
+) -> io::Result<()> {
+    let p = precision;
+    writeln!(
+        out,
+        "\nStarting {start_year}:    return    AK    AK net    AK tax    KF    KF tax"
+    )?;
+    for y in years {
+        writeln!(
+            out,
+            "{}:    {:.p$}    {:.p$}    {:.p$}    {:.p$}    {:.p$}    {:.p$}",
+            y.year,
+            y.return_ratio,
+            y.aktiekonto,
+            y.aktiekonto_net,
+            y.aktiekonto_tax,
+            y.kapitalförsäkring,
+            y.kapitalförsäkring_tax
+        )?;
+    }
+    Ok(())
+}
+
+/// Writes a table with one row per tax regime and the KF win rate of each
+/// holding length, `-` for a length without start years.
+pub fn write_tax_comparison(
+    out: &mut impl Write,
+    lengths: &[i32],
+    regimes: &[(&str, Results)],
+    precision: usize,
+) -> io::Result<()> {
+    let p = precision;
+    let width = regimes
+        .iter()
+        .map(|(name, _)| name.chars().count())
+        .chain(["Tax regime".len()])
+        .max()
+        .unwrap_or(0);
+    write!(out, "\n{:<width$}", "Tax regime")?;
+    for length in lengths {
+        write!(out, "    {length} years")?;
+    }
+    writeln!(out)?;
+    for (name, results) in regimes {
+        write!(out, "{name:<width$}")?;
+        for (length, summary) in lengths.iter().map(|l| (l, &results.summaries[l])) {
+            let rate = format_pct(summary.kf_win_rate.map(|r| r * 100.0), p, false);
+            let column = format!("{length} years").len();
+            write!(out, "    {rate:>column$}")?;
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+/// Widest bar of [`write_histogram`], in characters.
+const HISTOGRAM_WIDTH: usize = 50;
+
+/// Writes an ASCII histogram of [`SeriesEntry::kf_difference_pct`] over the
+/// start years of one holding length, in buckets of `bucket_width`
+/// percentage points. Bars are scaled so the fullest bucket is
+/// [`HISTOGRAM_WIDTH`] characters.
+pub fn write_histogram(
+    out: &mut impl Write,
+    len: i32,
+    entries: &[SeriesEntry],
+    bucket_width: f64,
+    precision: usize,
+) -> io::Result<()> {
+    let p = precision;
+    writeln!(out, "\n{len} years, KF minus AK:")?;
+    let differences: Vec<f64> = entries.iter().map(SeriesEntry::kf_difference_pct).collect();
+    let buckets = histogram(&differences, bucket_width);
+    let fullest = buckets.iter().map(|&(_, count)| count).max().unwrap_or(0);
+    let labels: Vec<String> = buckets
+        .iter()
+        .map(|&(lower, _)| format!("{lower:+.p$}% to {:+.p$}%", lower + bucket_width))
+        .collect();
+    let label_width = labels.iter().map(String::len).max().unwrap_or(0);
+    for (label, &(_, count)) in labels.iter().zip(&buckets) {
+        let bar = "#".repeat((count * HISTOGRAM_WIDTH).div_ceil(fullest));
+        writeln!(out, "{label:>label_width$}  {bar} {count}")?;
+    }
+    Ok(())
+}
+
+/// Writes `fields` as one delimited line, quoting any field that contains
+/// the delimiter, a quote or a line break.
+fn write_row(
+    out: &mut impl Write,
+    fields: impl IntoIterator<Item = String>,
+    delimiter: char,
+) -> io::Result<()> {
+    let fields: Vec<String> = fields
+        .into_iter()
+        .map(|field| {
+            if field.contains([delimiter, '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field
+            }
+        })
+        .collect();
+    writeln!(out, "{}", fields.join(&delimiter.to_string()))
+}
+
+/// Writes one CSV row per series entry, with a leading `index` column when
+/// `labelled` is set and trailing `drag_only` and `benchmark` columns when
+/// simulated. With more than one length an overview table follows, as in
+/// the text output.
+/// Fields are separated by `delimiter`, a tab giving TSV.
+pub fn write_csv(
+    out: &mut impl Write,
+    lengths: &[i32],
+    results: &[IndexSeries],
+    labelled: bool,
+    delimiter: char,
+) -> io::Result<()> {
+    let entries = || {
+        results
+            .iter()
+            .flat_map(|(_, results)| results.series.values().flatten())
+    };
+    let with_drag_only = entries().any(|e| e.drag_only.is_some());
+    let with_benchmark = entries().any(|e| e.benchmark.is_some());
+    let header = [
+        "length",
+        "start_year",
+        "aktiekonto",
+        "kapitalforsakring",
+        "isk",
+        "kapitalforsakring_tax",
+    ];
+    write_row(
+        out,
+        labelled
+            .then_some("index")
+            .into_iter()
+            .chain(header)
+            .chain(with_drag_only.then_some("drag_only"))
+            .chain(with_benchmark.then_some("benchmark"))
+            .map(String::from),
+        delimiter,
+    )?;
+    for (name, results) in results {
+        for &length in lengths {
+            for e in results.entries(length) {
+                let values = [
+                    length.to_string(),
+                    e.start_year.to_string(),
+                    e.aktiekonto.to_string(),
+                    e.kapitalförsäkring.to_string(),
+                    e.isk.to_string(),
+                    e.kapitalförsäkring_tax.to_string(),
+                ];
+                let drag_only = e.drag_only.map_or(String::new(), |d| d.to_string());
+                let benchmark = e.benchmark.map_or(String::new(), |b| b.to_string());
+                write_row(
+                    out,
+                    labelled
+                        .then(|| name.clone())
+                        .into_iter()
+                        .chain(values)
+                        .chain(with_drag_only.then_some(drag_only))
+                        .chain(with_benchmark.then_some(benchmark)),
+                    delimiter,
+                )?;
+            }
+        }
+    }
+    if lengths.len() > 1 {
+        write_csv_overview(out, lengths, results, labelled, delimiter)?;
+    }
+    Ok(())
+}
+
+/// Writes the overview of [`write_overview`] as a second CSV table after a
+/// blank line: one row per index and holding length with the average AK
+/// and KF final values, the KF win rate as a fraction and the average KF
+/// vs AK difference in percent. Missing values are empty.
+fn write_csv_overview(
+    out: &mut impl Write,
+    lengths: &[i32],
+    results: &[IndexSeries],
+    labelled: bool,
+    delimiter: char,
+) -> io::Result<()> {
+    let field = |value: Option<f64>| value.map_or(String::new(), |v| v.to_string());
+    writeln!(out)?;
+    let header = [
+        "length",
+        "mean_aktiekonto",
+        "mean_kapitalforsakring",
+        "kf_win_rate",
+        "mean_kf_difference",
+    ];
+    write_row(
+        out,
+        labelled
+            .then_some("index")
+            .into_iter()
+            .chain(header)
+            .map(String::from),
+        delimiter,
+    )?;
+    for (name, results) in results {
+        for &length in lengths {
+            let summary = &results.summaries[&length];
+            let values = [
+                length.to_string(),
+                field(summary.aktiekonto.mean),
+                field(summary.kapitalförsäkring.mean),
+                field(summary.kf_win_rate),
+                field(summary.mean_kf_difference),
+            ];
+            write_row(
+                out,
+                labelled.then(|| name.clone()).into_iter().chain(values),
+                delimiter,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes one GitHub-flavored markdown table per holding length, under a
+/// heading per index when `labelled` is set, ending in a row of averages.
+pub fn write_markdown(
+    out: &mut impl Write,
+    lengths: &[i32],
+    results: &[IndexSeries],
+    labelled: bool,
+    precision: usize,
+    amounts: &Amounts,
+) -> io::Result<()> {
+    let p = precision;
+    for (name, results) in results {
+        if labelled {
+            writeln!(out, "## {name}\n")?;
+        }
+        for &length in lengths {
+            let entries = results.entries(length);
+            writeln!(out, "### {length} years\n")?;
+            writeln!(out, "| Start year | AK | KF | KF vs AK |")?;
+            writeln!(out, "|---:|---:|---:|---:|")?;
+            for e in entries {
+                writeln!(
+                    out,
+                    "| {} | {} | {} | {:+.p$}% |",
+                    e.start_year,
+                    amounts.format(e.aktiekonto, p),
+                    amounts.format(e.kapitalförsäkring, p),
+                    e.kf_difference_pct()
+                )?;
+            }
+            let summary = &results.summaries[&length];
+            writeln!(
+                out,
+                "| **Average** | {} | {} | {} |\n",
+                amounts.format_stat(summary.aktiekonto.mean, p),
+                amounts.format_stat(summary.kapitalförsäkring.mean, p),
+                format_pct(summary.mean_kf_difference, p, true)
+            )?;
+        }
+        if lengths.len() > 1 {
+            writeln!(out, "### All lengths\n")?;
+            writeln!(out, "| Length | AK | KF | KF wins | KF vs AK |")?;
+            writeln!(out, "|---:|---:|---:|---:|---:|")?;
+            for &length in lengths {
+                let summary = &results.summaries[&length];
+                writeln!(
+                    out,
+                    "| {length} years | {} | {} | {} | {} |",
+                    amounts.format_stat(summary.aktiekonto.mean, p),
+                    amounts.format_stat(summary.kapitalförsäkring.mean, p),
+                    format_pct(summary.kf_win_rate.map(|r| r * 100.0), p, false),
+                    format_pct(summary.mean_kf_difference, p, true)
+                )?;
+            }
+            writeln!(out)?;
+        }
+    }
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct LengthResults<'a> {
+    entries: &'a [SeriesEntry],
+    summary: &'a SeriesSummary,
+}
+
+fn length_results<'a>(lengths: &[i32], results: &'a Results) -> BTreeMap<i32, LengthResults<'a>> {
+    lengths
+        .iter()
+        .map(|&length| {
+            let entries = results.entries(length);
+            let summary = &results.summaries[&length];
+            (length, LengthResults { entries, summary })
+        })
+        .collect()
+}
+
+/// One input file of a run, as listed in [`RunMeta`].
+#[derive(Debug, Clone, Serialize)]
+pub struct InputFile {
+    /// The flag the file was read for, e.g. `--slr`.
+    pub flag: String,
+    pub path: String,
+    /// `None` for stdin, the bundled demo data, or when the file system
+    /// does not keep modification times.
+    pub modified: Option<DateTime<Utc>>,
+}
+
+/// What the results of a run were computed from, written by [`write_json`]
+/// so an archived file describes itself.
+#[derive(Debug, Clone, Serialize)]
+pub struct RunMeta {
+    pub version: String,
+    pub generated_at: DateTime<Utc>,
+    pub from_year: i32,
+    pub to_year: i32,
+    pub inputs: Vec<InputFile>,
+    pub tax: TaxParams,
+    /// The account parameters, so that the run can be repeated.
+    pub simulation: SimulationParams,
+}
+
+#[derive(Serialize)]
+struct JsonOutput<'a, T> {
+    meta: &'a RunMeta,
+    results: T,
+}
+
+/// Writes `meta` and the results, which are by length, or by index name
+/// and then length when `labelled` is set.
+pub fn write_json(
+    out: &mut impl Write,
+    lengths: &[i32],
+    results: &[IndexSeries],
+    labelled: bool,
+    meta: &RunMeta,
+) -> io::Result<()> {
+    if labelled {
+        let by_index: BTreeMap<&str, BTreeMap<i32, LengthResults>> = results
+            .iter()
+            .map(|(name, results)| (name.as_str(), length_results(lengths, results)))
+            .collect();
+        let output = JsonOutput {
+            meta,
+            results: by_index,
+        };
+        serde_json::to_writer_pretty(&mut *out, &output)?;
+    } else {
+        for (_, results) in results {
+            let output = JsonOutput {
+                meta,
+                results: length_results(lengths, results),
+            };
+            serde_json::to_writer_pretty(&mut *out, &output)?;
+        }
+    }
+    writeln!(out)
+}
+
+/// Writes the combined records of every index in year order, under a header
+/// per index when `labelled` is set. Years missing from one input show its
+/// value as `0`.
+pub fn write_combined_text(
+    out: &mut impl Write,
+    results: &[IndexRecords],
+    labelled: bool,
+) -> io::Result<()> {
+    for (name, records) in results {
+        if labelled {
+            write_index_header(out, name)?;
+        }
+        writeln!(out, "year    omxs30    slr    avkastningsskatt")?;
+        for (year, r) in records {
+            writeln!(
+                out,
+                "{year}    {}    {}    {}",
+                r.omxs30, r.slr, r.avkastningsskatt
+            )?;
+        }
+    }
+    Ok(())
+}
+
+pub fn write_combined_csv(
+    out: &mut impl Write,
+    results: &[IndexRecords],
+    labelled: bool,
+    delimiter: char,
+) -> io::Result<()> {
+    let header = ["year", "omxs30", "slr", "avkastningsskatt"];
+    write_row(
+        out,
+        labelled
+            .then_some("index")
+            .into_iter()
+            .chain(header)
+            .map(String::from),
+        delimiter,
+    )?;
+    for (name, records) in results {
+        for (year, r) in records {
+            let values = [
+                year.to_string(),
+                r.omxs30.to_string(),
+                r.slr.to_string(),
+                r.avkastningsskatt.to_string(),
+            ];
+            write_row(
+                out,
+                labelled.then(|| name.clone()).into_iter().chain(values),
+                delimiter,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes the records by year, or by index name and then year when
+/// `labelled` is set.
+pub fn write_combined_json(
+    out: &mut impl Write,
+    results: &[IndexRecords],
+    labelled: bool,
+) -> io::Result<()> {
+    if labelled {
+        let by_index: BTreeMap<&str, &BTreeMap<i32, Record>> = results
+            .iter()
+            .map(|(name, records)| (name.as_str(), records))
+            .collect();
+        serde_json::to_writer_pretty(&mut *out, &by_index)?;
+    } else {
+        for (_, records) in results {
+            serde_json::to_writer_pretty(&mut *out, records)?;
+        }
+    }
+    writeln!(out)
+}
+
+pub fn write_combined_markdown(
+    out: &mut impl Write,
+    results: &[IndexRecords],
+    labelled: bool,
+) -> io::Result<()> {
+    for (name, records) in results {
+        if labelled {
+            writeln!(out, "## {name}\n")?;
+        }
+        writeln!(out, "| Year | OMXS30 | SLR | Avkastningsskatt |")?;
+        writeln!(out, "|---:|---:|---:|---:|")?;
+        for (year, r) in records {
+            writeln!(
+                out,
+                "| {year} | {} | {} | {} |",
+                r.omxs30, r.slr, r.avkastningsskatt
+            )?;
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+/// Writes the average AK and KF final values of every length, then the
+/// shortest length where KF beats AK on average, or that it never does
+/// within the `(from_year, to_year)` range.
+pub fn write_breakeven_text(
+    out: &mut impl Write,
+    results: &[IndexAverages],
+    labelled: bool,
+    range: (i32, i32),
+    precision: usize,
+) -> io::Result<()> {
+    let p = precision;
+    for (name, averages) in results {
+        if labelled {
+            write_index_header(out, name)?;
+        }
+        for a in averages {
+            writeln!(
+                out,
+                "{} years averages:    {:.p$}    {:.p$}",
+                a.length, a.aktiekonto, a.kapitalförsäkring
+            )?;
+        }
+        writeln!(out, "{}", breakeven_sentence(averages, range))?;
+    }
+    Ok(())
+}
+
+fn breakeven_sentence(averages: &[LengthAverages], (from_year, to_year): (i32, i32)) -> String {
+    match find_breakeven(averages) {
+        Some(length) => format!("KF beats AK on average from {length} years"),
+        None => format!(
+            "KF never beats AK on average within 1 to {} years ({from_year}..={to_year})",
+            to_year - from_year
+        ),
+    }
+}
+
+/// Writes one CSV row per index and length, with a `kf_ahead` column that
+/// is `true` where KF's average is above AK's.
+pub fn write_breakeven_csv(
+    out: &mut impl Write,
+    results: &[IndexAverages],
+    labelled: bool,
+    delimiter: char,
+) -> io::Result<()> {
+    let header = ["length", "aktiekonto", "kapitalforsakring", "kf_ahead"];
+    write_row(
+        out,
+        labelled
+            .then_some("index")
+            .into_iter()
+            .chain(header)
+            .map(String::from),
+        delimiter,
+    )?;
+    for (name, averages) in results {
+        for a in averages {
+            let values = [
+                a.length.to_string(),
+                a.aktiekonto.to_string(),
+                a.kapitalförsäkring.to_string(),
+                (a.kapitalförsäkring > a.aktiekonto).to_string(),
+            ];
+            write_row(
+                out,
+                labelled.then(|| name.clone()).into_iter().chain(values),
+                delimiter,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct Breakeven<'a> {
+    /// `None` when KF never beats AK on average.
+    breakeven: Option<i32>,
+    averages: &'a [LengthAverages],
+}
+
+impl<'a> Breakeven<'a> {
+    fn of(averages: &'a [LengthAverages]) -> Self {
+        Breakeven {
+            breakeven: find_breakeven(averages),
+            averages,
+        }
+    }
+}
+
+/// Writes the break-even length and the averages it was found from, by
+/// index name when `labelled` is set.
+pub fn write_breakeven_json(
+    out: &mut impl Write,
+    results: &[IndexAverages],
+    labelled: bool,
+) -> io::Result<()> {
+    if labelled {
+        let by_index: BTreeMap<&str, Breakeven> = results
+            .iter()
+            .map(|(name, averages)| (name.as_str(), Breakeven::of(averages)))
+            .collect();
+        serde_json::to_writer_pretty(&mut *out, &by_index)?;
+    } else {
+        for (_, averages) in results {
+            serde_json::to_writer_pretty(&mut *out, &Breakeven::of(averages))?;
+        }
+    }
+    writeln!(out)
+}
+
+/// Writes a table of the averages of every length per index, followed by
+/// the break-even sentence of [`write_breakeven_text`].
+pub fn write_breakeven_markdown(
+    out: &mut impl Write,
+    results: &[IndexAverages],
+    labelled: bool,
+    range: (i32, i32),
+    precision: usize,
+) -> io::Result<()> {
+    let p = precision;
+    for (name, averages) in results {
+        if labelled {
+            writeln!(out, "## {name}\n")?;
+        }
+        writeln!(out, "| Length | AK | KF |")?;
+        writeln!(out, "|---:|---:|---:|")?;
+        for a in averages {
+            writeln!(
+                out,
+                "| {} years | {:.p$} | {:.p$} |",
+                a.length, a.aktiekonto, a.kapitalförsäkring
+            )?;
+        }
+        writeln!(out, "\n{}\n", breakeven_sentence(averages, range))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use crate::inflation::deflate;
+use crate::simulate::{simulate, Record, SeriesEntry, SimulationParams};
+use crate::stats::bootstrap_mean;
+use crate::summary::{summarize, SeriesSummary};
+use rand::Rng;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Confidence level in percent of [`Results::bootstrap`].
+pub const BOOTSTRAP_LEVEL: f64 = 90.0;
+
+/// What [`run`] simulates: the holding lengths, the `(from_year, to_year)`
+/// range of start and end years, and the account parameters.
+#[derive(Debug, Clone)]
+pub struct RunConfig {
+    pub lengths: Vec<i32>,
+    pub range: (i32, i32),
+    pub params: SimulationParams,
+}
+
+/// The start years of every holding length and their summary statistics.
+#[derive(Debug, Serialize)]
+pub struct Results {
+    pub series: BTreeMap<i32, Vec<SeriesEntry>>,
+    /// One summary per length, including lengths without start years.
+    pub summaries: BTreeMap<i32, SeriesSummary>,
+}
+
+impl Results {
+    /// Summarizes `series` for each of `lengths`.
+    pub fn new(lengths: &[i32], series: BTreeMap<i32, Vec<SeriesEntry>>) -> Self {
+        let summaries = lengths
+            .iter()
+            .map(|&length| {
+                let entries = series.get(&length).map_or(&[][..], Vec::as_slice);
+                (length, summarize(length, entries))
+            })
+            .collect();
+        Results { series, summaries }
+    }
+
+    /// The start years of `length`, empty if it has none.
+    pub fn entries(&self, length: i32) -> &[SeriesEntry] {
+        self.series.get(&length).map_or(&[][..], Vec::as_slice)
+    }
+
+    /// Sets the [`BOOTSTRAP_LEVEL`] interval of the average KF vs AK
+    /// difference of every length from `resamples` draws of its start years.
+    /// The start years overlap, so they are not independent and the
+    /// interval is only a rough guide.
+    pub fn bootstrap(&mut self, resamples: usize, rng: &mut impl Rng) {
+        for (&length, summary) in &mut self.summaries {
+            let differences: Vec<f64> = self
+                .series
+                .get(&length)
+                .map_or(&[][..], Vec::as_slice)
+                .iter()
+                .map(SeriesEntry::kf_difference_pct)
+                .collect();
+            summary.kf_difference_interval =
+                bootstrap_mean(&differences, resamples, BOOTSTRAP_LEVEL, rng);
+        }
+    }
+
+    /// Converts the final values with [`deflate`] and summarizes them again.
+    pub fn deflate(
+        &mut self,
+        cpi_by_year: &BTreeMap<i32, f64>,
+        base_year: Option<i32>,
+    ) -> Result<(), String> {
+        deflate(&mut self.series, cpi_by_year, base_year)?;
+        let lengths: Vec<i32> = self.summaries.keys().copied().collect();
+        for length in lengths {
+            let summary = summarize(length, self.entries(length));
+            self.summaries.insert(length, summary);
+        }
+        Ok(())
+    }
+}
+
+/// Simulates every holding length of `config` on `records` and summarizes
+/// the outcome, without printing anything. Fails if
+/// [`SimulationParams::validate`] rejects the parameters or a year in the
+/// range has no record.
+pub fn run(config: &RunConfig, records: &BTreeMap<i32, Record>) -> Result<Results, String> {
+    config.params.validate()?;
+    let series = simulate(records, &config.lengths, config.range, &config.params)?;
+    Ok(Results::new(&config.lengths, series))
+}
@@ -0,0 +1,53 @@
+use calamine::{open_workbook_auto, Data, DataType, Reader};
+use std::path::Path;
+
+/// Extensions of the spreadsheet formats [`sheet_lines`] can read.
+pub const SPREADSHEET_EXTENSIONS: [&str; 5] = ["xlsx", "xlsm", "xlsb", "xls", "ods"];
+
+/// Whether `path` has one of the [`SPREADSHEET_EXTENSIONS`].
+pub fn is_spreadsheet(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            SPREADSHEET_EXTENSIONS
+                .iter()
+                .any(|known| ext.eq_ignore_ascii_case(known))
+        })
+}
+
+/// Reads the date and value columns (1-based) of `sheet`, or of the first
+/// sheet, as lines in the tab-separated format of the OMXS30 file, so they
+/// go through the same line parsers as text input. Cells formatted as dates
+/// are written as `YYYY-MM-DD`; every other cell as its text, leaving rows
+/// such as headers for the parser to reject. Fails for column 0.
+pub fn sheet_lines(
+    path: &Path,
+    sheet: Option<&str>,
+    date_column: usize,
+    value_column: usize,
+) -> Result<Vec<String>, String> {
+    if date_column == 0 || value_column == 0 {
+        return Err("spreadsheet columns count from 1".to_string());
+    }
+    let mut workbook = open_workbook_auto(path).map_err(|e| e.to_string())?;
+    let range = match sheet {
+        Some(name) => workbook.worksheet_range(name),
+        None => workbook
+            .worksheet_range_at(0)
+            .unwrap_or(Err(calamine::Error::Msg("the workbook has no sheets"))),
+    }
+    .map_err(|e| e.to_string())?;
+
+    let cell = |row: &[Data], column: usize| match row.get(column - 1) {
+        Some(data) if data.is_datetime() || data.is_datetime_iso() => data.as_date().map_or_else(
+            || data.to_string(),
+            |date| date.format("%Y-%m-%d").to_string(),
+        ),
+        Some(data) => data.to_string(),
+        None => String::new(),
+    };
+    Ok(range
+        .rows()
+        .map(|row| format!("{}\t{}", cell(row, date_column), cell(row, value_column)))
+        .collect())
+}
@@ -0,0 +1,913 @@
+use crate::stats::{cagr, irr, mean, sample_std_dev, sharpe_ratio};
+use crate::tax::{calculate_avkastningsskatt, TaxParams};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Record {
+    pub avkastningsskatt: f64,
+    pub omxs30: f64,
+    /// Statslåneränta in percent.
+    pub slr: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SeriesEntry {
+    pub start_year: i32,
+    pub aktiekonto: f64,
+    #[serde(rename = "kapitalforsakring")]
+    pub kapitalförsäkring: f64,
+    /// Deepest fall from a previous year-end high of the aktiekonto value
+    /// before tax, as a fraction.
+    pub aktiekonto_max_drawdown: f64,
+    #[serde(rename = "kapitalforsakring_max_drawdown")]
+    pub kapitalförsäkring_max_drawdown: f64,
+    /// Avkastningsskatt taken from the kapitalförsäkring over the holding
+    /// period, in the same units as its value.
+    #[serde(rename = "kapitalforsakring_tax")]
+    pub kapitalförsäkring_tax: f64,
+    /// Investeringssparkonto value: schablon taxed like the
+    /// kapitalförsäkring, but without its fee.
+    pub isk: f64,
+    pub isk_max_drawdown: f64,
+    /// Annualized return over the average SLR, divided by the standard
+    /// deviation of the yearly returns. `None` for holding periods shorter
+    /// than two years.
+    pub aktiekonto_sharpe: Option<f64>,
+    #[serde(rename = "kapitalforsakring_sharpe")]
+    pub kapitalförsäkring_sharpe: Option<f64>,
+    pub isk_sharpe: Option<f64>,
+    /// Sample standard deviation of the yearly log returns, the aktiekonto's
+    /// before capital-gains tax. `None` for holding periods shorter than two
+    /// years.
+    pub aktiekonto_volatility: Option<f64>,
+    #[serde(rename = "kapitalforsakring_volatility")]
+    pub kapitalförsäkring_volatility: Option<f64>,
+    pub isk_volatility: Option<f64>,
+    /// Value of the account that only pays
+    /// [`SimulationParams::drag_only_fee`], when one is simulated.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub drag_only: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub drag_only_max_drawdown: Option<f64>,
+    /// Value of the fixed-rate savings account, when one is simulated.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub benchmark: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub benchmark_max_drawdown: Option<f64>,
+    /// Money-weighted annual return of the contributions, as a fraction.
+    /// Only set with a [`ContributionPlan`]; a single deposit's is the CAGR.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aktiekonto_irr: Option<f64>,
+    #[serde(
+        rename = "kapitalforsakring_irr",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub kapitalförsäkring_irr: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub isk_irr: Option<f64>,
+    /// Amount invested up front, in the kronor of the values, when set;
+    /// otherwise the values are multipliers of a single unit. Deflating to
+    /// a base year sets it to what the unit is worth in that year.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub initial_capital: Option<f64>,
+    /// Whether the values include a [`ContributionPlan`]'s deposits, so
+    /// they are not a multiple of one amount and have no CAGR.
+    #[serde(skip)]
+    pub contributions: bool,
+}
+
+impl SeriesEntry {
+    /// `value` as a multiplier of the amount invested up front.
+    pub fn multiplier(&self, value: f64) -> f64 {
+        value / self.initial_capital.unwrap_or(1.0)
+    }
+
+    /// The compound annual growth rate of `value` over `length` years, as a
+    /// fraction. `None` with contributions, whose IRR is the rate to use.
+    pub fn cagr(&self, value: f64, length: i32) -> Option<f64> {
+        (!self.contributions).then(|| cagr(self.multiplier(value), length))
+    }
+
+    /// Percentage by which the kapitalförsäkring beat the aktiekonto,
+    /// negative when it trailed.
+    pub fn kf_difference_pct(&self) -> f64 {
+        (self.kapitalförsäkring / self.aktiekonto - 1.0) * 100.0
+    }
+}
+
+/// Joins the yearly index and SLR values into one record per year. Years
+/// missing from either input get `0.0` for that value.
+pub fn combine_records(
+    omxs30_by_year: &BTreeMap<i32, f64>,
+    slr_by_year: &BTreeMap<i32, f64>,
+    tax: &TaxParams,
+) -> BTreeMap<i32, Record> {
+    let mut combined_records = BTreeMap::new();
+    let years: BTreeSet<_> = omxs30_by_year.keys().chain(slr_by_year.keys()).collect();
+
+    for &year in years {
+        let omxs30 = *omxs30_by_year.get(&year).unwrap_or(&0.0);
+        let slr = *slr_by_year.get(&year).unwrap_or(&0.0);
+
+        combined_records.insert(
+            year,
+            Record {
+                avkastningsskatt: calculate_avkastningsskatt(year, slr, tax),
+                omxs30,
+                slr,
+            },
+        );
+    }
+
+    combined_records
+}
+
+/// Turns each of `years` into a year with no index return and no
+/// avkastningsskatt, by scaling the index values from that year on. The
+/// returns of the other years are unchanged. Years without a value for the
+/// year before are left as they are.
+pub fn neutralize_years(records: &mut BTreeMap<i32, Record>, years: &[i32]) {
+    for &year in years {
+        let (Some(previous), Some(record)) = (records.get(&(year - 1)), records.get(&year)) else {
+            continue;
+        };
+        if record.omxs30 == 0.0 {
+            continue;
+        }
+        let scale = previous.omxs30 / record.omxs30;
+        for (_, record) in records.range_mut(year..) {
+            record.omxs30 *= scale;
+        }
+        if let Some(record) = records.get_mut(&year) {
+            record.avkastningsskatt = 0.0;
+        }
+    }
+}
+
+/// How often a contribution is made in [`ContributionPlan`] mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum ContributionInterval {
+    Yearly,
+    Monthly,
+}
+
+impl ContributionInterval {
+    fn per_year(self) -> usize {
+        match self {
+            ContributionInterval::Yearly => 1,
+            ContributionInterval::Monthly => 12,
+        }
+    }
+}
+
+/// Invest `amount` at the start of every interval instead of a single unit
+/// at the start year. The index only has yearly values, so in monthly mode
+/// each year's return is spread evenly (geometrically) over its months.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ContributionPlan {
+    pub amount: f64,
+    pub interval: ContributionInterval,
+}
+
+impl ContributionPlan {
+    /// Internal rate of return of following the plan for `years` years and
+    /// ending with `final_value`.
+    fn irr(&self, years: i32, final_value: f64) -> Option<f64> {
+        let per_year = self.interval.per_year();
+        let cash_flows: Vec<(f64, f64)> = (0..years as usize * per_year)
+            .map(|i| (i as f64 / per_year as f64, -self.amount))
+            .chain([(years as f64, final_value)])
+            .collect();
+        irr(&cash_flows)
+    }
+}
+
+/// When aktiekonto gains are taxed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum AkRealize {
+    /// The whole position is sold, and the gain taxed, at the end.
+    #[default]
+    Sell,
+    /// The position is kept, so the reported value is before tax.
+    Hold,
+    /// The position is sold and rebought at the end of every year, so each
+    /// year's gain is taxed then instead of deferred to the end.
+    Annual,
+}
+
+/// How a [`Benchmark`] savings account is taxed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum BenchmarkTax {
+    /// The interest is not taxed.
+    #[default]
+    None,
+    /// Schablon taxed like the kapitalförsäkring, but without its fee.
+    Schablon,
+}
+
+/// A savings account compounding at a fixed rate, to compare the index
+/// accounts against. Deposits follow the same [`ContributionPlan`].
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Benchmark {
+    /// Annual interest in percent.
+    pub rate: f64,
+    pub tax: BenchmarkTax,
+}
+
+/// Tunable assumptions for [`simulate`].
+#[derive(Debug, Clone, Serialize)]
+pub struct SimulationParams {
+    /// Annual kapitalförsäkring fee in percent of the account value.
+    pub kf_fee: f64,
+    /// Regular contributions; `None` invests `initial_capital` up front.
+    pub contribution: Option<ContributionPlan>,
+    /// Tax on aktiekonto gains when the position is sold.
+    pub capital_gains_rate: f64,
+    pub ak_realize: AkRealize,
+    /// Annual dividend in percent of the value at the start of each year,
+    /// added to the price index return. A flat yield is a rough stand-in
+    /// for a total-return index. The kapitalförsäkring and ISK dividends
+    /// are covered by the schablon tax; on the aktiekonto they are taxed at
+    /// `capital_gains_rate` when received and the rest is reinvested.
+    pub dividend_yield: f64,
+    /// Annual fee in percent of an account that follows the index with
+    /// no tax at all, to tell the cost of the kapitalförsäkring fee from
+    /// that of its schablon tax.
+    pub drag_only_fee: Option<f64>,
+    pub benchmark: Option<Benchmark>,
+    /// Month (1 to 12) of the first simulated year in which the up-front amount
+    /// is invested. See [`YearStep::prorated`].
+    pub entry_month: u32,
+    /// Amount invested up front without a contribution plan, in kronor.
+    /// `None` invests a single unit, which makes the values multipliers.
+    pub initial_capital: Option<f64>,
+    /// Compute the Sharpe ratios from continuously compounded yearly
+    /// returns, `ln(end / start)`, instead of simple ones. The volatility is
+    /// always that of the log returns, and the accounts always compound
+    /// with simple returns.
+    pub log_returns: bool,
+    /// Start years to leave out of [`simulate`] and the other start-year
+    /// loops. See [`neutralize_years`] for leaving years out of the
+    /// compounding instead.
+    pub excluded_start_years: Vec<i32>,
+}
+
+impl Default for SimulationParams {
+    fn default() -> Self {
+        SimulationParams {
+            kf_fee: 0.0,
+            contribution: None,
+            capital_gains_rate: 0.206,
+            ak_realize: AkRealize::Sell,
+            dividend_yield: 0.0,
+            drag_only_fee: None,
+            benchmark: None,
+            entry_month: 1,
+            initial_capital: None,
+            log_returns: false,
+            excluded_start_years: Vec::new(),
+        }
+    }
+}
+
+impl SimulationParams {
+    /// Checks the values that the simulation cannot work with, which the
+    /// command line rejects but a config file or a library caller can set.
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=12).contains(&self.entry_month) {
+            return Err(format!(
+                "entry_month must be 1 to 12, got {}",
+                self.entry_month
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// One purchase into the aktiekonto. Lots are kept apart so the
+/// capital-gains tax can be taken on their aggregate unrealized gain.
+struct Contribution {
+    basis: f64,
+    value: f64,
+}
+
+/// Checks that every year in `range` has a positive index value. Years that
+/// only appear in the SLR data are combined with an index value of `0.0`,
+/// which would otherwise turn the yearly return ratios into `inf` or `NaN`.
+pub fn check_index_values(
+    records: &BTreeMap<i32, Record>,
+    range: (i32, i32),
+) -> Result<(), String> {
+    let bad_years: Vec<String> = (range.0..=range.1)
+        .filter(|year| records.get(year).is_none_or(|r| r.omxs30 <= 0.0))
+        .map(|year| year.to_string())
+        .collect();
+    if bad_years.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "no OMXS30 value for years: {}",
+            bad_years.join(", ")
+        ))
+    }
+}
+
+/// Market conditions for one simulated year.
+#[derive(Debug, Clone, Copy)]
+pub struct YearStep {
+    /// Index value at the end of the year divided by the value a year earlier.
+    pub diff: f64,
+    pub avkastningsskatt: f64,
+    /// Statslåneränta in percent, used as the risk-free rate.
+    pub slr: f64,
+}
+
+impl YearStep {
+    /// The step from the end of `year - 1` to the end of `year`, or an
+    /// error naming whichever of the two years has no record.
+    pub fn of_year(records: &BTreeMap<i32, Record>, year: i32) -> Result<Self, String> {
+        let record = |year| {
+            records
+                .get(&year)
+                .ok_or_else(|| format!("no record for {year}"))
+        };
+        let previous = record(year - 1)?;
+        let current = record(year)?;
+        Ok(YearStep {
+            diff: current.omxs30 / previous.omxs30,
+            avkastningsskatt: current.avkastningsskatt,
+            slr: current.slr,
+        })
+    }
+
+    /// The step of every year after `range.0` up to `range.1`, or an error
+    /// naming the first year without a record.
+    pub(crate) fn of_range(
+        records: &BTreeMap<i32, Record>,
+        range: (i32, i32),
+    ) -> Result<BTreeMap<i32, Self>, String> {
+        ((range.0 + 1)..=range.1)
+            .map(|year| Ok((year, YearStep::of_year(records, year)?)))
+            .collect()
+    }
+
+    /// The step for only the last `share` of the year. The return and the
+    /// avkastningsskatt are scaled linearly, as if both accrued evenly over
+    /// the year, so the index path within the year is ignored.
+    pub fn prorated(self, share: f64) -> Self {
+        YearStep {
+            diff: 1.0 + (self.diff - 1.0) * share,
+            avkastningsskatt: self.avkastningsskatt * share,
+            slr: self.slr,
+        }
+    }
+
+    /// The index return plus the dividend yield in `params`, received on the
+    /// value at the start of the year.
+    fn total_diff(&self, params: &SimulationParams) -> f64 {
+        self.diff + params.dividend_yield / 100.0
+    }
+}
+
+/// Running peak and deepest relative fall from it.
+#[derive(Default)]
+struct Drawdown {
+    peak: f64,
+    max: f64,
+}
+
+impl Drawdown {
+    fn update(&mut self, value: f64) {
+        self.peak = self.peak.max(value);
+        if self.peak > 0.0 {
+            self.max = self.max.max(1.0 - value / self.peak);
+        }
+    }
+}
+
+/// Yearly returns of one account. A year's contributions are counted as if
+/// they were made at its start.
+#[derive(Default)]
+struct Returns {
+    yearly: Vec<f64>,
+}
+
+impl Returns {
+    fn push(&mut self, start: f64, end: f64) {
+        if start > 0.0 {
+            self.yearly.push(end / start - 1.0);
+        }
+    }
+
+    /// Sharpe ratio of the compounded yearly returns, with the growth scaled
+    /// by `end_factor` for anything taken out at the end. With `log_returns`
+    /// the excess is of the average log return over `ln(1 + risk_free)`, per
+    /// unit of log-return volatility.
+    fn sharpe(&self, end_factor: f64, risk_free: f64, log_returns: bool) -> Option<f64> {
+        if log_returns {
+            let logs: Vec<f64> = self.yearly.iter().map(|r| r.ln_1p()).collect();
+            let annual_return = (logs.iter().sum::<f64>() + end_factor.ln()) / logs.len() as f64;
+            return sharpe_ratio(annual_return, risk_free.ln_1p(), &logs);
+        }
+        let growth = self.yearly.iter().map(|r| 1.0 + r).product::<f64>() * end_factor;
+        let annual_return = growth.powf(1.0 / self.yearly.len() as f64) - 1.0;
+        sharpe_ratio(annual_return, risk_free, &self.yearly)
+    }
+
+    fn volatility(&self) -> Option<f64> {
+        let log_returns: Vec<f64> = self.yearly.iter().map(|r| r.ln_1p()).collect();
+        sample_std_dev(&log_returns)
+    }
+}
+
+/// Balances of every account type over one simulated holding period.
+pub(crate) struct Accounts {
+    ak_lots: Vec<Contribution>,
+    kf_sum: f64,
+    isk_sum: f64,
+    drag_only_sum: f64,
+    benchmark_sum: f64,
+    ak_drawdown: Drawdown,
+    kf_drawdown: Drawdown,
+    isk_drawdown: Drawdown,
+    drag_only_drawdown: Drawdown,
+    benchmark_drawdown: Drawdown,
+    ak_returns: Returns,
+    kf_returns: Returns,
+    isk_returns: Returns,
+    /// SLR of every simulated year, as a fraction.
+    risk_free: Vec<f64>,
+    /// Capital-gains tax paid on dividends and yearly realizations so far.
+    ak_tax_paid: f64,
+    /// Avkastningsskatt paid so far.
+    kf_tax_paid: f64,
+}
+
+impl Accounts {
+    pub(crate) fn new(params: &SimulationParams) -> Self {
+        let mut accounts = Accounts {
+            ak_lots: Vec::new(),
+            kf_sum: 0.0,
+            isk_sum: 0.0,
+            drag_only_sum: 0.0,
+            benchmark_sum: 0.0,
+            ak_drawdown: Drawdown::default(),
+            kf_drawdown: Drawdown::default(),
+            isk_drawdown: Drawdown::default(),
+            drag_only_drawdown: Drawdown::default(),
+            benchmark_drawdown: Drawdown::default(),
+            ak_returns: Returns::default(),
+            kf_returns: Returns::default(),
+            isk_returns: Returns::default(),
+            risk_free: Vec::new(),
+            ak_tax_paid: 0.0,
+            kf_tax_paid: 0.0,
+        };
+        if params.contribution.is_none() {
+            let capital = params.initial_capital.unwrap_or(1.0);
+            accounts.ak_lots.push(Contribution {
+                basis: capital,
+                value: capital,
+            });
+            accounts.kf_sum = capital;
+            accounts.isk_sum = capital;
+            accounts.drag_only_sum = capital;
+            accounts.benchmark_sum = capital;
+        }
+        accounts.ak_drawdown.update(accounts.ak_sum());
+        accounts.kf_drawdown.update(accounts.kf_sum);
+        accounts.isk_drawdown.update(accounts.isk_sum);
+        accounts.drag_only_drawdown.update(accounts.drag_only_sum);
+        accounts.benchmark_drawdown.update(accounts.benchmark_sum);
+        accounts
+    }
+
+    /// `step` as [`Accounts::step`] applies it next, prorated in the first
+    /// year when investing after January.
+    pub(crate) fn applied(&self, step: YearStep, params: &SimulationParams) -> YearStep {
+        // risk_free has one value per year already simulated.
+        if self.risk_free.is_empty() && params.entry_month > 1 {
+            step.prorated((13 - params.entry_month) as f64 / 12.0)
+        } else {
+            step
+        }
+    }
+
+    pub(crate) fn step(&mut self, step: YearStep, params: &SimulationParams) {
+        let step = self.applied(step, params);
+        let deposits = params
+            .contribution
+            .map_or(0.0, |plan| plan.amount * plan.interval.per_year() as f64);
+        let ak_start = self.ak_sum() + deposits;
+        let kf_start = self.kf_sum + deposits;
+        let isk_start = self.isk_sum + deposits;
+        let mut kapitalunderlag = self.kf_sum;
+        let total_diff = step.total_diff(params);
+
+        match params.contribution {
+            None => {
+                let ak_dividend = self.ak_sum() * (total_diff - step.diff);
+                for lot in &mut self.ak_lots {
+                    lot.value *= step.diff;
+                }
+                self.reinvest_dividend(ak_dividend, params);
+                self.kf_sum *= total_diff;
+            }
+            Some(plan) => {
+                let steps = plan.interval.per_year();
+                let step_diff = step.diff.powf(1.0 / steps as f64);
+                let step_total_diff = total_diff.powf(1.0 / steps as f64);
+                for i in 0..steps {
+                    self.ak_lots.push(Contribution {
+                        basis: plan.amount,
+                        value: plan.amount,
+                    });
+                    self.kf_sum += plan.amount;
+                    // Deposits in the second half of the year only count
+                    // half towards the kapitalunderlag.
+                    kapitalunderlag += if i * 2 < steps {
+                        plan.amount
+                    } else {
+                        plan.amount / 2.0
+                    };
+
+                    let ak_dividend = self.ak_sum() * (step_total_diff - step_diff);
+                    for lot in &mut self.ak_lots {
+                        lot.value *= step_diff;
+                    }
+                    self.reinvest_dividend(ak_dividend, params);
+                    self.kf_sum *= step_total_diff;
+                }
+            }
+        }
+
+        let kf_tax = kapitalunderlag * step.avkastningsskatt;
+        self.kf_sum -= kf_tax;
+        self.kf_tax_paid += kf_tax;
+        self.kf_sum *= 1.0 - params.kf_fee / 100.0;
+
+        if params.ak_realize == AkRealize::Annual {
+            self.realize_ak_gains(params);
+        }
+
+        self.ak_drawdown.update(self.ak_sum());
+        self.kf_drawdown.update(self.kf_sum);
+
+        self.step_isk(step, params);
+        self.step_drag_only(step, params);
+        self.step_benchmark(step, params);
+
+        self.ak_returns.push(ak_start, self.ak_sum());
+        self.kf_returns.push(kf_start, self.kf_sum);
+        self.isk_returns.push(isk_start, self.isk_sum);
+        self.risk_free.push(step.slr / 100.0);
+    }
+
+    /// The ISK kapitalunderlag is a quarter of the values at the start of
+    /// each quarter plus the year's deposits, where the kapitalförsäkring uses
+    /// the value at the start of the year. The return within a year is spread
+    /// evenly over its quarters, or months in monthly contribution mode.
+    fn step_isk(&mut self, step: YearStep, params: &SimulationParams) {
+        let (deposits_per_year, amount) = match params.contribution {
+            Some(plan) => (plan.interval.per_year(), plan.amount),
+            None => (1, 0.0),
+        };
+        let steps = deposits_per_year.max(4);
+        let step_diff = step.total_diff(params).powf(1.0 / steps as f64);
+
+        let mut quarter_values = 0.0;
+        let mut deposits = 0.0;
+        for i in 0..steps {
+            if (i * 4).is_multiple_of(steps) {
+                quarter_values += self.isk_sum;
+            }
+            if (i * deposits_per_year).is_multiple_of(steps) {
+                self.isk_sum += amount;
+                deposits += amount;
+            }
+            self.isk_sum *= step_diff;
+        }
+        self.isk_sum -= (quarter_values + deposits) / 4.0 * step.avkastningsskatt;
+
+        self.isk_drawdown.update(self.isk_sum);
+    }
+
+    /// Grows the drag-only account, if any, with the index return spread
+    /// evenly over the contribution intervals, and takes its fee at the end
+    /// of the year like the kapitalförsäkring fee.
+    fn step_drag_only(&mut self, step: YearStep, params: &SimulationParams) {
+        let Some(fee) = params.drag_only_fee else {
+            return;
+        };
+        let (steps, amount) = match params.contribution {
+            Some(plan) => (plan.interval.per_year(), plan.amount),
+            None => (1, 0.0),
+        };
+        let step_diff = step.total_diff(params).powf(1.0 / steps as f64);
+
+        for _ in 0..steps {
+            self.drag_only_sum += amount;
+            self.drag_only_sum *= step_diff;
+        }
+        self.drag_only_sum *= 1.0 - fee / 100.0;
+
+        self.drag_only_drawdown.update(self.drag_only_sum);
+    }
+
+    /// Grows the [`Benchmark`] account, if any, at its fixed rate spread
+    /// evenly over the contribution intervals, and takes the schablon tax
+    /// on the same kapitalunderlag as the kapitalförsäkring if it is taxed.
+    fn step_benchmark(&mut self, step: YearStep, params: &SimulationParams) {
+        let Some(benchmark) = params.benchmark else {
+            return;
+        };
+        let (steps, amount) = match params.contribution {
+            Some(plan) => (plan.interval.per_year(), plan.amount),
+            None => (1, 0.0),
+        };
+        let step_growth = (1.0 + benchmark.rate / 100.0).powf(1.0 / steps as f64);
+
+        let mut kapitalunderlag = self.benchmark_sum;
+        for i in 0..steps {
+            self.benchmark_sum += amount;
+            kapitalunderlag += if i * 2 < steps { amount } else { amount / 2.0 };
+            self.benchmark_sum *= step_growth;
+        }
+        if benchmark.tax == BenchmarkTax::Schablon {
+            self.benchmark_sum -= kapitalunderlag * step.avkastningsskatt;
+        }
+
+        self.benchmark_drawdown.update(self.benchmark_sum);
+    }
+
+    /// Sells and rebuys the whole aktiekonto, paying capital-gains tax on its
+    /// gain over the basis. A loss is kept in the basis instead, so it
+    /// offsets the gains of later years.
+    fn realize_ak_gains(&mut self, params: &SimulationParams) {
+        let value = self.ak_sum();
+        let basis = self.ak_basis();
+        if value > basis {
+            let tax = (value - basis) * params.capital_gains_rate;
+            self.ak_tax_paid += tax;
+            let after_tax = value - tax;
+            self.ak_lots = vec![Contribution {
+                basis: after_tax,
+                value: after_tax,
+            }];
+        }
+    }
+
+    /// Buys a new aktiekonto lot with what is left of `dividend` after tax.
+    fn reinvest_dividend(&mut self, dividend: f64, params: &SimulationParams) {
+        if dividend > 0.0 {
+            let tax = dividend * params.capital_gains_rate;
+            self.ak_tax_paid += tax;
+            let net = dividend - tax;
+            self.ak_lots.push(Contribution {
+                basis: net,
+                value: net,
+            });
+        }
+    }
+
+    /// Aktiekonto value before any capital-gains tax.
+    pub(crate) fn ak_sum(&self) -> f64 {
+        self.ak_lots.iter().map(|lot| lot.value).sum()
+    }
+
+    /// What was paid for the aktiekonto lots still held.
+    fn ak_basis(&self) -> f64 {
+        self.ak_lots.iter().map(|lot| lot.basis).sum()
+    }
+
+    /// Aktiekonto value, after paying capital-gains tax if the position is
+    /// sold.
+    pub(crate) fn aktiekonto(&self, params: &SimulationParams) -> f64 {
+        let ak_sum = self.ak_sum();
+        match params.ak_realize {
+            AkRealize::Sell | AkRealize::Annual => {
+                let ak_basis = self.ak_basis();
+                ak_sum - ((ak_sum - ak_basis) * params.capital_gains_rate).max(0.0)
+            }
+            AkRealize::Hold => ak_sum,
+        }
+    }
+
+    pub(crate) fn kapitalförsäkring(&self) -> f64 {
+        self.kf_sum
+    }
+
+    pub(crate) fn isk(&self) -> f64 {
+        self.isk_sum
+    }
+
+    /// Tax paid so far from the aktiekonto and the kapitalförsäkring, not
+    /// counting the capital-gains tax due when the aktiekonto is sold.
+    pub(crate) fn taxes_paid(&self) -> (f64, f64) {
+        (self.ak_tax_paid, self.kf_tax_paid)
+    }
+
+    /// Takes `net` kronor out of the aktiekonto, selling an equal share of
+    /// every lot so the withdrawal realizes its proportional part of the
+    /// gain, plus enough to pay the capital-gains tax on it. Returns false,
+    /// and empties the account, when it cannot cover the withdrawal.
+    pub(crate) fn withdraw_aktiekonto(&mut self, net: f64, params: &SimulationParams) -> bool {
+        let value = self.ak_sum();
+        let basis = self.ak_basis();
+        let gain_share = if value > 0.0 {
+            ((value - basis) / value).max(0.0)
+        } else {
+            0.0
+        };
+        let gross = net / (1.0 - gain_share * params.capital_gains_rate);
+        if gross > value {
+            self.ak_lots.clear();
+            return false;
+        }
+        let kept = 1.0 - gross / value;
+        for lot in &mut self.ak_lots {
+            lot.value *= kept;
+            lot.basis *= kept;
+        }
+        true
+    }
+
+    /// Withdrawals from the kapitalförsäkring are not taxed; the schablon
+    /// tax in [`Accounts::step`] has already been paid.
+    pub(crate) fn withdraw_kapitalförsäkring(&mut self, amount: f64) -> bool {
+        withdraw_from(&mut self.kf_sum, amount)
+    }
+
+    pub(crate) fn withdraw_isk(&mut self, amount: f64) -> bool {
+        withdraw_from(&mut self.isk_sum, amount)
+    }
+
+    /// The first account whose value is not a finite, positive number.
+    fn invalid_value(&self) -> Option<(&'static str, f64)> {
+        [
+            ("aktiekonto", self.ak_sum()),
+            ("kapitalförsäkring", self.kf_sum),
+            ("ISK", self.isk_sum),
+        ]
+        .into_iter()
+        .find(|&(_, value)| !(value.is_finite() && value > 0.0))
+    }
+
+    /// Sharpe ratios of the aktiekonto, kapitalförsäkring and ISK so far,
+    /// against the average SLR. The aktiekonto return includes the
+    /// capital-gains tax when the position is sold.
+    fn sharpe_ratios(&self, params: &SimulationParams) -> [Option<f64>; 3] {
+        let Some(risk_free) = mean(&self.risk_free) else {
+            return [None; 3];
+        };
+        let ak_sum = self.ak_sum();
+        let ak_end_factor = if ak_sum > 0.0 {
+            self.aktiekonto(params) / ak_sum
+        } else {
+            1.0
+        };
+        [
+            self.ak_returns
+                .sharpe(ak_end_factor, risk_free, params.log_returns),
+            self.kf_returns.sharpe(1.0, risk_free, params.log_returns),
+            self.isk_returns.sharpe(1.0, risk_free, params.log_returns),
+        ]
+    }
+
+    /// Volatility of the aktiekonto, kapitalförsäkring and ISK so far.
+    fn volatilities(&self) -> [Option<f64>; 3] {
+        [
+            self.ak_returns.volatility(),
+            self.kf_returns.volatility(),
+            self.isk_returns.volatility(),
+        ]
+    }
+}
+
+/// Takes `amount` out of `sum`, or empties it and returns false when it does
+/// not cover the amount.
+fn withdraw_from(sum: &mut f64, amount: f64) -> bool {
+    if amount > *sum {
+        *sum = 0.0;
+        return false;
+    }
+    *sum -= amount;
+    true
+}
+
+/// Runs one simulation per start year in `range.0..range.1`, compounding up
+/// to `range.1`, and collects the outcome after each of the given holding
+/// lengths. Fails, naming the year, when a year in `range` has no record.
+///
+/// An account value that stops being a finite, positive number means the
+/// records are broken. A warning naming the start year and year is logged
+/// and that start year is left out, in debug and release builds alike.
+///
+/// Every simulated year is logged at debug level with its return ratio,
+/// avkastningsskatt and the account values after it.
+///
+/// Start years are simulated in parallel on the rayon thread pool; each
+/// series is sorted by start year so the result does not depend on
+/// scheduling.
+pub fn simulate(
+    records: &BTreeMap<i32, Record>,
+    lengths: &[i32],
+    range: (i32, i32),
+    params: &SimulationParams,
+) -> Result<BTreeMap<i32, Vec<SeriesEntry>>, String> {
+    let (from_year, to_year) = range;
+    let steps = YearStep::of_range(records, range)?;
+
+    let partial: Vec<Vec<(i32, SeriesEntry)>> = (from_year..to_year)
+        .into_par_iter()
+        .filter(|start_year| !params.excluded_start_years.contains(start_year))
+        .map(|start_year| {
+            let mut entries = Vec::new();
+            let mut accounts = Accounts::new(params);
+
+            for year in (start_year + 1)..=to_year {
+                let step = steps[&year];
+                accounts.step(step, params);
+                log::debug!(
+                    "start year {start_year}, {year}: return ratio {:.6}, avkastningsskatt {:.6}, AK {:.6} (basis {:.6}), KF {:.6}, ISK {:.6}",
+                    step.diff,
+                    step.avkastningsskatt,
+                    accounts.ak_sum(),
+                    accounts.ak_basis(),
+                    accounts.kf_sum,
+                    accounts.isk_sum
+                );
+                if let Some((account, value)) = accounts.invalid_value() {
+                    let message = format!(
+                        "start year {start_year}: the {account} value is {value} after {year}"
+                    );
+                    log::warn!("{message}, leaving the start year out");
+                    entries.clear();
+                    break;
+                }
+
+                let year_count = year - start_year;
+
+                if lengths.contains(&year_count) {
+                    let [aktiekonto_sharpe, kapitalförsäkring_sharpe, isk_sharpe] =
+                        accounts.sharpe_ratios(params);
+                    let [aktiekonto_volatility, kapitalförsäkring_volatility, isk_volatility] =
+                        accounts.volatilities();
+                    let irr = |value: f64| params.contribution?.irr(year_count, value);
+                    entries.push((
+                        year_count,
+                        SeriesEntry {
+                            start_year,
+                            aktiekonto: accounts.aktiekonto(params),
+                            kapitalförsäkring: accounts.kapitalförsäkring(),
+                            aktiekonto_max_drawdown: accounts.ak_drawdown.max,
+                            kapitalförsäkring_max_drawdown: accounts.kf_drawdown.max,
+                            kapitalförsäkring_tax: accounts.kf_tax_paid,
+                            isk: accounts.isk(),
+                            isk_max_drawdown: accounts.isk_drawdown.max,
+                            aktiekonto_sharpe,
+                            kapitalförsäkring_sharpe,
+                            isk_sharpe,
+                            aktiekonto_volatility,
+                            kapitalförsäkring_volatility,
+                            isk_volatility,
+                            drag_only: params.drag_only_fee.map(|_| accounts.drag_only_sum),
+                            drag_only_max_drawdown: params
+                                .drag_only_fee
+                                .map(|_| accounts.drag_only_drawdown.max),
+                            benchmark: params.benchmark.map(|_| accounts.benchmark_sum),
+                            benchmark_max_drawdown: params
+                                .benchmark
+                                .map(|_| accounts.benchmark_drawdown.max),
+                            aktiekonto_irr: irr(accounts.aktiekonto(params)),
+                            kapitalförsäkring_irr: irr(accounts.kapitalförsäkring()),
+                            isk_irr: irr(accounts.isk()),
+                            initial_capital: params
+                                .initial_capital
+                                .filter(|_| params.contribution.is_none()),
+                            contributions: params.contribution.is_some(),
+                        },
+                    ));
+                }
+            }
+            entries
+        })
+        .collect();
+
+    let mut series: BTreeMap<i32, Vec<SeriesEntry>> = BTreeMap::new();
+    for (length, entry) in partial.into_iter().flatten() {
+        series.entry(length).or_default().push(entry);
+    }
+    for entries in series.values_mut() {
+        entries.sort_by_key(|e| e.start_year);
+    }
+    Ok(series)
+}
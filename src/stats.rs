@@ -0,0 +1,217 @@
+use rand::{Rng, RngExt};
+use serde::Serialize;
+
+pub fn mean(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    Some(values.iter().sum::<f64>() / values.len() as f64)
+}
+
+/// Middle value after sorting, or the average of the two middle values for
+/// an even number of values.
+pub fn median(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        Some((sorted[mid - 1] + sorted[mid]) / 2.0)
+    } else {
+        Some(sorted[mid])
+    }
+}
+
+/// Sample (n - 1) standard deviation. Needs at least two values.
+pub fn sample_std_dev(values: &[f64]) -> Option<f64> {
+    if values.len() < 2 {
+        return None;
+    }
+    let mean = mean(values)?;
+    let sum_sq: f64 = values.iter().map(|v| (v - mean).powi(2)).sum();
+    Some((sum_sq / (values.len() - 1) as f64).sqrt())
+}
+
+/// Nth root of the product of `values`, computed through logarithms so long
+/// series do not overflow. `None` when empty or when any value is not a
+/// positive number, as a zero or `NaN` multiplier has no meaningful root.
+pub fn geometric_mean(values: &[f64]) -> Option<f64> {
+    if values.is_empty() || values.iter().any(|&v| !(v > 0.0 && v.is_finite())) {
+        return None;
+    }
+    Some((values.iter().map(|v| v.ln()).sum::<f64>() / values.len() as f64).exp())
+}
+
+/// Compound annual growth rate of a multiplier reached over `years` years.
+pub fn cagr(multiplier: f64, years: i32) -> f64 {
+    multiplier.powf(1.0 / years as f64) - 1.0
+}
+
+/// Counts `values` in buckets of `width` aligned to multiples of it, from
+/// the bucket of the smallest value to that of the largest, including the
+/// empty ones in between. Each bucket is its lower bound and count, and
+/// includes its lower bound but not its upper.
+pub fn histogram(values: &[f64], width: f64) -> Vec<(f64, usize)> {
+    let buckets: Vec<i64> = values.iter().map(|v| (v / width).floor() as i64).collect();
+    let (Some(&first), Some(&last)) = (buckets.iter().min(), buckets.iter().max()) else {
+        return Vec::new();
+    };
+    (first..=last)
+        .map(|bucket| {
+            let count = buckets.iter().filter(|&&b| b == bucket).count();
+            (bucket as f64 * width, count)
+        })
+        .collect()
+}
+
+/// Linearly interpolated percentile, with `p` between 0 and 100.
+pub fn percentile(values: &[f64], p: f64) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let rank = p / 100.0 * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    Some(sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64))
+}
+
+/// Range that holds the true value with `level` percent confidence.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ConfidenceInterval {
+    pub level: f64,
+    pub lower: f64,
+    pub upper: f64,
+}
+
+/// Percentile bootstrap interval of the mean of `values`: the mean of each
+/// of `resamples` draws of as many values with replacement, cut at the
+/// tails outside `level` percent. `None` without values or resamples.
+pub fn bootstrap_mean(
+    values: &[f64],
+    resamples: usize,
+    level: f64,
+    rng: &mut impl Rng,
+) -> Option<ConfidenceInterval> {
+    if values.is_empty() || resamples == 0 {
+        return None;
+    }
+    let means: Vec<f64> = (0..resamples)
+        .map(|_| {
+            let sum: f64 = (0..values.len())
+                .map(|_| values[rng.random_range(0..values.len())])
+                .sum();
+            sum / values.len() as f64
+        })
+        .collect();
+    let tail = (100.0 - level) / 2.0;
+    Some(ConfidenceInterval {
+        level,
+        lower: percentile(&means, tail)?,
+        upper: percentile(&means, 100.0 - tail)?,
+    })
+}
+
+/// Excess of `annual_return` over `risk_free` per unit of volatility of
+/// `returns`. `None` with fewer than two returns or no volatility.
+pub fn sharpe_ratio(annual_return: f64, risk_free: f64, returns: &[f64]) -> Option<f64> {
+    let volatility = sample_std_dev(returns)?;
+    if volatility == 0.0 {
+        return None;
+    }
+    Some((annual_return - risk_free) / volatility)
+}
+
+/// Least-squares line through a set of points.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct LinearFit {
+    pub slope: f64,
+    pub intercept: f64,
+    /// Share of the variance in y explained by the line, 1 when y does not
+    /// vary at all.
+    pub r_squared: f64,
+}
+
+/// Ordinary least-squares fit of `points` as `(x, y)`. `None` with fewer
+/// than two points or when every x is the same.
+pub fn linear_regression(points: &[(f64, f64)]) -> Option<LinearFit> {
+    if points.len() < 2 {
+        return None;
+    }
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+    let ss_xx: f64 = points.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+    let ss_xy: f64 = points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+    let ss_yy: f64 = points.iter().map(|p| (p.1 - mean_y).powi(2)).sum();
+    if ss_xx == 0.0 {
+        return None;
+    }
+    let slope = ss_xy / ss_xx;
+    Some(LinearFit {
+        slope,
+        intercept: mean_y - slope * mean_x,
+        r_squared: if ss_yy == 0.0 {
+            1.0
+        } else {
+            ss_xy * ss_xy / (ss_xx * ss_yy)
+        },
+    })
+}
+
+/// Net present value of `(years, amount)` cash flows discounted at `rate`.
+fn npv(cash_flows: &[(f64, f64)], rate: f64) -> f64 {
+    cash_flows
+        .iter()
+        .map(|&(t, amount)| amount * (1.0 + rate).powf(-t))
+        .sum()
+}
+
+/// Internal rate of return of `(years, amount)` cash flows, deposits being
+/// negative: the annual rate that makes their net present value zero. Tries
+/// Newton's method first and falls back to bisection between -99% and
+/// 1000% when it does not converge. `None` when there is no root in that
+/// range.
+pub fn irr(cash_flows: &[(f64, f64)]) -> Option<f64> {
+    const TOLERANCE: f64 = 1e-10;
+    const LOW: f64 = -0.99;
+    const HIGH: f64 = 10.0;
+
+    let mut rate = 0.1;
+    for _ in 0..50 {
+        let value = npv(cash_flows, rate);
+        let derivative: f64 = cash_flows
+            .iter()
+            .map(|&(t, amount)| -t * amount * (1.0 + rate).powf(-t - 1.0))
+            .sum();
+        if derivative == 0.0 || !derivative.is_finite() {
+            break;
+        }
+        let next = rate - value / derivative;
+        if !(LOW..=HIGH).contains(&next) {
+            break;
+        }
+        if (next - rate).abs() < TOLERANCE {
+            return Some(next);
+        }
+        rate = next;
+    }
+
+    let (mut low, mut high) = (LOW, HIGH);
+    let low_value = npv(cash_flows, low);
+    if low_value.signum() == npv(cash_flows, high).signum() {
+        return None;
+    }
+    while high - low > TOLERANCE {
+        let mid = (low + high) / 2.0;
+        if npv(cash_flows, mid).signum() == low_value.signum() {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    Some((low + high) / 2.0)
+}
@@ -0,0 +1,230 @@
+use crate::simulate::SeriesEntry;
+use crate::stats::{
+    geometric_mean, linear_regression, mean, median, sample_std_dev, ConfidenceInterval, LinearFit,
+};
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct ColumnSummary {
+    pub mean: Option<f64>,
+    /// `None` if any final value is not positive.
+    pub geometric_mean: Option<f64>,
+    pub median: Option<f64>,
+    pub std_dev: Option<f64>,
+    /// Average compound annual growth rate, as a fraction.
+    pub mean_cagr: Option<f64>,
+    pub mean_max_drawdown: Option<f64>,
+    /// Average over the start years that have a Sharpe ratio.
+    pub mean_sharpe: Option<f64>,
+    /// Average over the start years that have a volatility.
+    pub mean_volatility: Option<f64>,
+    /// Average internal rate of return, with contributions only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mean_irr: Option<f64>,
+}
+
+impl ColumnSummary {
+    fn of(
+        values: &[f64],
+        drawdowns: &[f64],
+        sharpes: &[Option<f64>],
+        volatilities: &[Option<f64>],
+        irrs: &[Option<f64>],
+        cagrs: &[Option<f64>],
+    ) -> Self {
+        let cagrs: Vec<f64> = cagrs.iter().flatten().copied().collect();
+        let sharpes: Vec<f64> = sharpes.iter().flatten().copied().collect();
+        let volatilities: Vec<f64> = volatilities.iter().flatten().copied().collect();
+        let irrs: Vec<f64> = irrs.iter().flatten().copied().collect();
+        ColumnSummary {
+            mean: mean(values),
+            geometric_mean: geometric_mean(values),
+            median: median(values),
+            std_dev: sample_std_dev(values),
+            mean_cagr: mean(&cagrs),
+            mean_max_drawdown: mean(drawdowns),
+            mean_sharpe: mean(&sharpes),
+            mean_volatility: mean(&volatilities),
+            mean_irr: mean(&irrs),
+        }
+    }
+}
+
+/// The outcome of one start year, kept for the extremes in [`SeriesSummary`].
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct StartYearOutcome {
+    pub start_year: i32,
+    pub aktiekonto: f64,
+    #[serde(rename = "kapitalforsakring")]
+    pub kapitalförsäkring: f64,
+}
+
+impl StartYearOutcome {
+    fn of(e: &SeriesEntry) -> Self {
+        StartYearOutcome {
+            start_year: e.start_year,
+            aktiekonto: e.aktiekonto,
+            kapitalförsäkring: e.kapitalförsäkring,
+        }
+    }
+
+    /// KF minus AK final value.
+    pub fn kf_advantage(&self) -> f64 {
+        self.kapitalförsäkring - self.aktiekonto
+    }
+}
+
+/// Aggregate statistics for one holding length. Advantages are the winner's
+/// percentage lead over the loser, averaged over the start years it won.
+#[derive(Debug, Serialize)]
+pub struct SeriesSummary {
+    pub count: usize,
+    pub aktiekonto: ColumnSummary,
+    #[serde(rename = "kapitalforsakring")]
+    pub kapitalförsäkring: ColumnSummary,
+    pub isk: ColumnSummary,
+    /// Present when every start year has a drag-only value.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub drag_only: Option<ColumnSummary>,
+    /// Present when every start year has a benchmark value.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub benchmark: Option<ColumnSummary>,
+    pub kf_wins: usize,
+    pub ak_wins: usize,
+    /// Share of the start years KF won, as a fraction.
+    pub kf_win_rate: Option<f64>,
+    pub kf_average_advantage: Option<f64>,
+    pub ak_average_advantage: Option<f64>,
+    /// Average of [`SeriesEntry::kf_difference_pct`] over all start years.
+    pub mean_kf_difference: Option<f64>,
+    /// Bootstrap interval of `mean_kf_difference`, set by
+    /// [`crate::Results::bootstrap`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kf_difference_interval: Option<ConfidenceInterval>,
+    /// Average of [`SeriesEntry::kapitalförsäkring_tax`] over all start years.
+    #[serde(rename = "mean_kapitalforsakring_tax")]
+    pub mean_kapitalförsäkring_tax: Option<f64>,
+    /// Start years with the largest and smallest KF minus AK final value.
+    pub best_for_kf: Option<StartYearOutcome>,
+    pub worst_for_kf: Option<StartYearOutcome>,
+    /// KF minus AK final value regressed on the start year; a positive
+    /// slope means KF's advantage grew for later start years.
+    pub kf_advantage_trend: Option<LinearFit>,
+}
+
+pub fn summarize(length: i32, series: &[SeriesEntry]) -> SeriesSummary {
+    let aktiekonto: Vec<f64> = series.iter().map(|e| e.aktiekonto).collect();
+    let kapitalförsäkring: Vec<f64> = series.iter().map(|e| e.kapitalförsäkring).collect();
+    let ak_drawdowns: Vec<f64> = series.iter().map(|e| e.aktiekonto_max_drawdown).collect();
+    let kf_drawdowns: Vec<f64> = series
+        .iter()
+        .map(|e| e.kapitalförsäkring_max_drawdown)
+        .collect();
+    let isk: Vec<f64> = series.iter().map(|e| e.isk).collect();
+    let isk_drawdowns: Vec<f64> = series.iter().map(|e| e.isk_max_drawdown).collect();
+    let ak_sharpes: Vec<Option<f64>> = series.iter().map(|e| e.aktiekonto_sharpe).collect();
+    let kf_sharpes: Vec<Option<f64>> = series.iter().map(|e| e.kapitalförsäkring_sharpe).collect();
+    let isk_sharpes: Vec<Option<f64>> = series.iter().map(|e| e.isk_sharpe).collect();
+    let ak_volatilities: Vec<Option<f64>> =
+        series.iter().map(|e| e.aktiekonto_volatility).collect();
+    let kf_volatilities: Vec<Option<f64>> = series
+        .iter()
+        .map(|e| e.kapitalförsäkring_volatility)
+        .collect();
+    let isk_volatilities: Vec<Option<f64>> = series.iter().map(|e| e.isk_volatility).collect();
+    let ak_irrs: Vec<Option<f64>> = series.iter().map(|e| e.aktiekonto_irr).collect();
+    let kf_irrs: Vec<Option<f64>> = series.iter().map(|e| e.kapitalförsäkring_irr).collect();
+    let isk_irrs: Vec<Option<f64>> = series.iter().map(|e| e.isk_irr).collect();
+    let drag_only: Option<Vec<f64>> = series.iter().map(|e| e.drag_only).collect();
+    let drag_only_drawdowns: Option<Vec<f64>> =
+        series.iter().map(|e| e.drag_only_max_drawdown).collect();
+    let benchmark: Option<Vec<f64>> = series.iter().map(|e| e.benchmark).collect();
+    let benchmark_drawdowns: Option<Vec<f64>> =
+        series.iter().map(|e| e.benchmark_max_drawdown).collect();
+
+    let kf_advantages: Vec<f64> = series
+        .iter()
+        .filter(|e| e.kapitalförsäkring > e.aktiekonto)
+        .map(|e| (e.kapitalförsäkring / e.aktiekonto - 1.0) * 100.0)
+        .collect();
+    let ak_advantages: Vec<f64> = series
+        .iter()
+        .filter(|e| e.kapitalförsäkring <= e.aktiekonto)
+        .map(|e| (e.aktiekonto / e.kapitalförsäkring - 1.0) * 100.0)
+        .collect();
+
+    let kf_differences: Vec<f64> = series.iter().map(SeriesEntry::kf_difference_pct).collect();
+    let kf_taxes: Vec<f64> = series.iter().map(|e| e.kapitalförsäkring_tax).collect();
+
+    let by_advantage = |a: &&SeriesEntry, b: &&SeriesEntry| {
+        (a.kapitalförsäkring - a.aktiekonto).total_cmp(&(b.kapitalförsäkring - b.aktiekonto))
+    };
+    let best_for_kf = series.iter().max_by(by_advantage).map(StartYearOutcome::of);
+    let worst_for_kf = series.iter().min_by(by_advantage).map(StartYearOutcome::of);
+
+    let advantage_by_year: Vec<(f64, f64)> = series
+        .iter()
+        .map(|e| (e.start_year as f64, e.kapitalförsäkring - e.aktiekonto))
+        .collect();
+
+    // Each entry's own CAGR, as the initial capital is in the kronor of
+    // its values, which differ between start years after deflating.
+    let cagrs = |value: fn(&SeriesEntry) -> Option<f64>| -> Vec<Option<f64>> {
+        series
+            .iter()
+            .map(|e| value(e).and_then(|v| e.cagr(v, length)))
+            .collect()
+    };
+
+    SeriesSummary {
+        count: series.len(),
+        aktiekonto: ColumnSummary::of(
+            &aktiekonto,
+            &ak_drawdowns,
+            &ak_sharpes,
+            &ak_volatilities,
+            &ak_irrs,
+            &cagrs(|e| Some(e.aktiekonto)),
+        ),
+        kapitalförsäkring: ColumnSummary::of(
+            &kapitalförsäkring,
+            &kf_drawdowns,
+            &kf_sharpes,
+            &kf_volatilities,
+            &kf_irrs,
+            &cagrs(|e| Some(e.kapitalförsäkring)),
+        ),
+        isk: ColumnSummary::of(
+            &isk,
+            &isk_drawdowns,
+            &isk_sharpes,
+            &isk_volatilities,
+            &isk_irrs,
+            &cagrs(|e| Some(e.isk)),
+        ),
+        drag_only: drag_only
+            .zip(drag_only_drawdowns)
+            .filter(|(values, _)| !values.is_empty())
+            .map(|(values, drawdowns)| {
+                ColumnSummary::of(&values, &drawdowns, &[], &[], &[], &cagrs(|e| e.drag_only))
+            }),
+        // A fixed rate has no volatility, nor a Sharpe ratio to take of it.
+        benchmark: benchmark
+            .zip(benchmark_drawdowns)
+            .filter(|(values, _)| !values.is_empty())
+            .map(|(values, drawdowns)| {
+                ColumnSummary::of(&values, &drawdowns, &[], &[], &[], &cagrs(|e| e.benchmark))
+            }),
+        kf_wins: kf_advantages.len(),
+        kf_win_rate: (!series.is_empty()).then(|| kf_advantages.len() as f64 / series.len() as f64),
+        ak_wins: ak_advantages.len(),
+        kf_average_advantage: mean(&kf_advantages),
+        ak_average_advantage: mean(&ak_advantages),
+        mean_kf_difference: mean(&kf_differences),
+        mean_kapitalförsäkring_tax: mean(&kf_taxes),
+        kf_difference_interval: None,
+        best_for_kf,
+        worst_for_kf,
+        kf_advantage_trend: linear_regression(&advantage_by_year),
+    }
+}
@@ -0,0 +1,121 @@
+use crate::simulate::{Accounts, AkRealize, Record, SimulationParams, YearStep};
+use crate::stats::mean;
+use rayon::prelude::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// The best time to move one start year's money from the aktiekonto into a
+/// kapitalförsäkring, within one holding length.
+#[derive(Debug, Serialize)]
+pub struct SwitchEntry {
+    pub start_year: i32,
+    /// Years held in the aktiekonto before switching: 0 is KF throughout and
+    /// the holding length is AK throughout.
+    pub switch_after: i32,
+    /// Final value with the switch made at `switch_after`.
+    pub value: f64,
+    pub aktiekonto: f64,
+    #[serde(rename = "kapitalforsakring")]
+    pub kapitalförsäkring: f64,
+}
+
+/// Average of [`SwitchEntry::switch_after`] over all start years.
+pub fn mean_switch_after(entries: &[SwitchEntry]) -> Option<f64> {
+    let switches: Vec<f64> = entries.iter().map(|e| e.switch_after as f64).collect();
+    mean(&switches)
+}
+
+/// For each start year and holding length, tries every year to switch from
+/// the aktiekonto to a kapitalförsäkring and keeps the one with the highest
+/// final value. Switching sells the position, so its gain is taxed once at
+/// `capital_gains_rate`, and the rest is invested in the kapitalförsäkring
+/// for the remaining years. The aktiekonto is sold at the end of the holding
+/// length when it is never switched.
+///
+/// Only a single investment up front is simulated; contributions are left
+/// out. Ties go to the earliest switch. Fails, naming the year, when a
+/// year in `range` has no record.
+pub fn optimize_switch(
+    records: &BTreeMap<i32, Record>,
+    lengths: &[i32],
+    range: (i32, i32),
+    params: &SimulationParams,
+) -> Result<BTreeMap<i32, Vec<SwitchEntry>>, String> {
+    let (from_year, to_year) = range;
+    let steps = YearStep::of_range(records, range)?;
+    let single = SimulationParams {
+        contribution: None,
+        ak_realize: AkRealize::Sell,
+        ..params.clone()
+    };
+
+    let partial: Vec<(i32, SwitchEntry)> = (from_year..to_year)
+        .into_par_iter()
+        .filter(|start_year| !params.excluded_start_years.contains(start_year))
+        .flat_map_iter(|start_year| {
+            let (single, steps) = (&single, &steps);
+            lengths
+                .iter()
+                .filter(move |&&length| start_year + length <= to_year)
+                .map(move |&length| {
+                    // Net aktiekonto value if sold after each number of years.
+                    let mut accounts = Accounts::new(single);
+                    let mut sold = vec![accounts.aktiekonto(single)];
+                    for year in (start_year + 1)..=(start_year + length) {
+                        accounts.step(steps[&year], single);
+                        sold.push(accounts.aktiekonto(single));
+                    }
+
+                    let finals: Vec<f64> = (0..=length)
+                        .map(|switch_after| {
+                            let capital = sold[switch_after as usize];
+                            if switch_after == length {
+                                return capital;
+                            }
+                            // Entry month only prorates the very first year.
+                            let kf_params = SimulationParams {
+                                initial_capital: Some(capital),
+                                entry_month: if switch_after == 0 {
+                                    single.entry_month
+                                } else {
+                                    1
+                                },
+                                ..single.clone()
+                            };
+                            let mut kf = Accounts::new(&kf_params);
+                            for year in (start_year + switch_after + 1)..=(start_year + length) {
+                                kf.step(steps[&year], &kf_params);
+                            }
+                            kf.kapitalförsäkring()
+                        })
+                        .collect();
+
+                    let mut best = 0;
+                    for (switch_after, &value) in finals.iter().enumerate() {
+                        if value > finals[best] {
+                            best = switch_after;
+                        }
+                    }
+                    (
+                        length,
+                        SwitchEntry {
+                            start_year,
+                            switch_after: best as i32,
+                            value: finals[best],
+                            aktiekonto: finals[length as usize],
+                            kapitalförsäkring: finals[0],
+                        },
+                    )
+                })
+        })
+        .collect();
+
+    let mut series: BTreeMap<i32, Vec<SwitchEntry>> = BTreeMap::new();
+    for (length, entry) in partial {
+        series.entry(length).or_default().push(entry);
+    }
+    for entries in series.values_mut() {
+        entries.sort_by_key(|e| e.start_year);
+    }
+    Ok(series)
+}
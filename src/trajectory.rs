@@ -0,0 +1,57 @@
+use crate::simulate::{Accounts, Record, SimulationParams, YearStep};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// The aktiekonto and kapitalförsäkring balances at the end of one year of a
+/// single start year's simulation.
+#[derive(Debug, Serialize)]
+pub struct TrajectoryYear {
+    pub year: i32,
+    /// Index return ratio applied for the year, prorated in a first year
+    /// invested after January.
+    pub return_ratio: f64,
+    /// Aktiekonto value before the capital-gains tax due on selling.
+    pub aktiekonto: f64,
+    /// Aktiekonto value as counted by [`crate::simulate`] for a holding
+    /// length ending this year.
+    pub aktiekonto_net: f64,
+    /// Capital-gains tax paid during the year on dividends and yearly
+    /// realizations.
+    pub aktiekonto_tax: f64,
+    #[serde(rename = "kapitalforsakring")]
+    pub kapitalförsäkring: f64,
+    /// Avkastningsskatt taken during the year.
+    #[serde(rename = "kapitalforsakring_tax")]
+    pub kapitalförsäkring_tax: f64,
+}
+
+/// Simulates `start_year` like [`crate::simulate`] and keeps every year's
+/// balances up to `to_year`, instead of only those at the end of each
+/// holding length. Fails, naming the year, when a year has no record.
+pub fn trajectory(
+    records: &BTreeMap<i32, Record>,
+    start_year: i32,
+    to_year: i32,
+    params: &SimulationParams,
+) -> Result<Vec<TrajectoryYear>, String> {
+    let steps = YearStep::of_range(records, (start_year, to_year))?;
+    let mut accounts = Accounts::new(params);
+    let mut years = Vec::new();
+    for year in (start_year + 1)..=to_year {
+        let step = steps[&year];
+        let return_ratio = accounts.applied(step, params).diff;
+        let (ak_paid, kf_paid) = accounts.taxes_paid();
+        accounts.step(step, params);
+        let (ak_total, kf_total) = accounts.taxes_paid();
+        years.push(TrajectoryYear {
+            year,
+            return_ratio,
+            aktiekonto: accounts.ak_sum(),
+            aktiekonto_net: accounts.aktiekonto(params),
+            aktiekonto_tax: ak_total - ak_paid,
+            kapitalförsäkring: accounts.kapitalförsäkring(),
+            kapitalförsäkring_tax: kf_total - kf_paid,
+        });
+    }
+    Ok(years)
+}
@@ -0,0 +1,81 @@
+use chrono::{Datelike, NaiveDate};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Year-over-year index ratios outside `MIN_YEARLY_RATIO..=MAX_YEARLY_RATIO`
+/// are more likely a data error than a real market move.
+pub const MAX_YEARLY_RATIO: f64 = 3.0;
+pub const MIN_YEARLY_RATIO: f64 = 0.3;
+
+/// Plausibility limits for a raw index series.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Limits {
+    pub min_value: Option<f64>,
+    pub max_value: Option<f64>,
+    /// First and last year of the analysis, inclusive.
+    pub window: Option<(i32, i32)>,
+}
+
+/// A suspicious observation found by [`find_outliers`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outlier {
+    Value { date: NaiveDate, value: f64 },
+    Date { date: NaiveDate },
+    Ratio { year: i32, ratio: f64 },
+}
+
+impl fmt::Display for Outlier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outlier::Value { date, value } => {
+                write!(f, "{date}: value {value} is outside the plausible range")
+            }
+            Outlier::Date { date } => write!(f, "{date} is outside the analysis window"),
+            Outlier::Ratio { year, ratio } => write!(
+                f,
+                "{year}: the index changed by a factor of {ratio:.2} from the year before"
+            ),
+        }
+    }
+}
+
+/// Flags values outside `limits`, dates outside its window and years in the
+/// window whose ratio to the previous year is implausibly large or small.
+/// Nothing is removed; the caller decides whether to warn or stop.
+pub fn find_outliers(
+    records: &[(NaiveDate, f64)],
+    by_year: &BTreeMap<i32, f64>,
+    limits: &Limits,
+) -> Vec<Outlier> {
+    let mut outliers = Vec::new();
+    for &(date, value) in records {
+        if limits.min_value.is_some_and(|min| value < min)
+            || limits.max_value.is_some_and(|max| value > max)
+        {
+            outliers.push(Outlier::Value { date, value });
+        }
+        if limits
+            .window
+            .is_some_and(|(from, to)| !(from..=to).contains(&date.year()))
+        {
+            outliers.push(Outlier::Date { date });
+        }
+    }
+
+    for (&year, &value) in by_year {
+        if limits
+            .window
+            .is_some_and(|(from, to)| year <= from || year > to)
+        {
+            continue;
+        }
+        let Some(&previous) = by_year.get(&(year - 1)) else {
+            continue;
+        };
+        let ratio = value / previous;
+        if !(MIN_YEARLY_RATIO..=MAX_YEARLY_RATIO).contains(&ratio) {
+            outliers.push(Outlier::Ratio { year, ratio });
+        }
+    }
+    outliers
+}
@@ -0,0 +1,103 @@
+use crate::simulate::{Accounts, AkRealize, Record, SimulationParams, YearStep};
+use rayon::prelude::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// How long each account keeps paying out after one accumulation period.
+/// A count is the number of full yearly withdrawals made; `None` means the
+/// account could still pay at the end of the data.
+#[derive(Debug, Serialize)]
+pub struct WithdrawalEntry {
+    pub start_year: i32,
+    /// Years of data after the accumulation period.
+    pub years_available: i32,
+    pub aktiekonto_years: Option<i32>,
+    #[serde(rename = "kapitalforsakring_years")]
+    pub kapitalförsäkring_years: Option<i32>,
+    pub isk_years: Option<i32>,
+}
+
+/// Accumulates like [`crate::simulate`] for each holding length, then keeps
+/// compounding without contributions up to `range.1` while taking out
+/// `rate` percent of each account's value at the end of the accumulation
+/// every year. The withdrawal is a fixed net amount, made at the end of
+/// each year after its return and tax. The aktiekonto value it is based on
+/// is after capital-gains tax, as if sold, and every withdrawal from it pays
+/// tax on the gain it realizes.
+///
+/// Start years whose accumulation ends at `range.1` leave no year to
+/// withdraw in and are left out. Fails, naming the year, when a year in
+/// `range` has no record.
+pub fn simulate_withdrawals(
+    records: &BTreeMap<i32, Record>,
+    lengths: &[i32],
+    range: (i32, i32),
+    params: &SimulationParams,
+    rate: f64,
+) -> Result<BTreeMap<i32, Vec<WithdrawalEntry>>, String> {
+    let (from_year, to_year) = range;
+    let steps = YearStep::of_range(records, range)?;
+    let spending = SimulationParams {
+        contribution: None,
+        ak_realize: AkRealize::Sell,
+        ..params.clone()
+    };
+
+    let partial: Vec<(i32, WithdrawalEntry)> = (from_year..to_year)
+        .into_par_iter()
+        .filter(|start_year| !params.excluded_start_years.contains(start_year))
+        .flat_map_iter(|start_year| {
+            let (spending, steps) = (&spending, &steps);
+            lengths
+                .iter()
+                .filter(move |&&length| start_year + length < to_year)
+                .map(move |&length| {
+                    let mut accounts = Accounts::new(params);
+                    let retirement_year = start_year + length;
+                    for year in (start_year + 1)..=retirement_year {
+                        accounts.step(steps[&year], params);
+                    }
+
+                    let rate = rate / 100.0;
+                    let ak_amount = accounts.aktiekonto(spending) * rate;
+                    let kf_amount = accounts.kapitalförsäkring() * rate;
+                    let isk_amount = accounts.isk() * rate;
+                    let (mut ak_years, mut kf_years, mut isk_years) = (None, None, None);
+                    for year in (retirement_year + 1)..=to_year {
+                        accounts.step(steps[&year], spending);
+                        let paid = year - retirement_year - 1;
+                        if ak_years.is_none() && !accounts.withdraw_aktiekonto(ak_amount, spending)
+                        {
+                            ak_years = Some(paid);
+                        }
+                        if kf_years.is_none() && !accounts.withdraw_kapitalförsäkring(kf_amount) {
+                            kf_years = Some(paid);
+                        }
+                        if isk_years.is_none() && !accounts.withdraw_isk(isk_amount) {
+                            isk_years = Some(paid);
+                        }
+                    }
+
+                    (
+                        length,
+                        WithdrawalEntry {
+                            start_year,
+                            years_available: to_year - retirement_year,
+                            aktiekonto_years: ak_years,
+                            kapitalförsäkring_years: kf_years,
+                            isk_years,
+                        },
+                    )
+                })
+        })
+        .collect();
+
+    let mut series: BTreeMap<i32, Vec<WithdrawalEntry>> = BTreeMap::new();
+    for (length, entry) in partial {
+        series.entry(length).or_default().push(entry);
+    }
+    for entries in series.values_mut() {
+        entries.sort_by_key(|e| e.start_year);
+    }
+    Ok(series)
+}
@@ -0,0 +1,210 @@
+use chrono::{Datelike, NaiveDate};
+use serde::Deserialize;
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+
+/// Which observation of a year represents that year.
+///
+/// The first trading day of a year directly follows the last trading day of
+/// the previous one, so switching from `Last` to `First` moves every yearly
+/// return window one observation later rather than a whole year.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum YearAnchor {
+    First,
+    #[default]
+    Last,
+}
+
+/// Reduces dated observations to one value per year, picking the first or
+/// last observation of each year by date.
+pub fn values_by_year(records: &[(NaiveDate, f64)], anchor: YearAnchor) -> BTreeMap<i32, f64> {
+    let mut sorted = records.to_vec();
+    sorted.sort_by_key(|&(date, _)| date);
+
+    let mut by_year = BTreeMap::new();
+    for (date, value) in sorted {
+        match anchor {
+            YearAnchor::First => {
+                by_year.entry(date.year()).or_insert(value);
+            }
+            YearAnchor::Last => {
+                by_year.insert(date.year(), value);
+            }
+        }
+    }
+    by_year
+}
+
+/// Which SLR observations set the avkastningsskatt of a year.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum SlrReference {
+    /// The last rate of November 30 of the year before, which is the rate
+    /// Skatteverket uses for the schablonintäkt.
+    Nov30,
+    /// The rate at the end of the year itself, picked by the [`YearAnchor`].
+    #[default]
+    YearEnd,
+    /// The average of the year's observations, the årsgenomsnitt.
+    Average,
+}
+
+/// Reduces dated SLR observations to one rate per year as `reference`
+/// says. With [`SlrReference::Nov30`] each rate is keyed by the year it is
+/// used for, so the year after the one it was observed in.
+pub fn values_by_reference(
+    records: &[(NaiveDate, f64)],
+    reference: SlrReference,
+    anchor: YearAnchor,
+) -> BTreeMap<i32, f64> {
+    match reference {
+        SlrReference::YearEnd => values_by_year(records, anchor),
+        SlrReference::Nov30 => {
+            let mut sorted = records.to_vec();
+            sorted.sort_by_key(|&(date, _)| date);
+            let mut by_year = BTreeMap::new();
+            for (date, value) in sorted {
+                if (date.month(), date.day()) <= (11, 30) {
+                    by_year.insert(date.year() + 1, value);
+                }
+            }
+            by_year
+        }
+        SlrReference::Average => {
+            let mut sums: BTreeMap<i32, (f64, usize)> = BTreeMap::new();
+            for &(date, value) in records {
+                let (sum, count) = sums.entry(date.year()).or_default();
+                *sum += value;
+                *count += 1;
+            }
+            sums.into_iter()
+                .map(|(year, (sum, count))| (year, sum / count as f64))
+                .collect()
+        }
+    }
+}
+
+/// Which row is kept when a date appears more than once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum OnDuplicate {
+    /// Reject the input, listing every repeated date.
+    Error,
+    /// Keep the row that comes first in the input.
+    First,
+    /// Keep the row that comes last in the input.
+    #[default]
+    Last,
+}
+
+/// Keeps one row per date of `records`, which are in input order, and
+/// returns them sorted by date.
+pub fn dedup_dates(
+    records: &[(NaiveDate, f64)],
+    on_duplicate: OnDuplicate,
+) -> Result<Vec<(NaiveDate, f64)>, String> {
+    let mut by_date: BTreeMap<NaiveDate, f64> = BTreeMap::new();
+    let mut duplicates = Vec::new();
+    for &(date, value) in records {
+        match by_date.entry(date) {
+            Entry::Vacant(entry) => {
+                entry.insert(value);
+            }
+            Entry::Occupied(mut entry) => {
+                duplicates.push(date);
+                if on_duplicate == OnDuplicate::Last {
+                    entry.insert(value);
+                }
+            }
+        }
+    }
+
+    if on_duplicate == OnDuplicate::Error && !duplicates.is_empty() {
+        duplicates.sort();
+        duplicates.dedup();
+        let dates: Vec<String> = duplicates.iter().map(|d| d.to_string()).collect();
+        return Err(format!("duplicate dates {}", dates.join(", ")));
+    }
+    Ok(by_date.into_iter().collect())
+}
+
+/// How years missing between the first and last year of a series are
+/// treated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+pub enum Fill {
+    /// Leave gaps alone, and report them as an error.
+    #[default]
+    None,
+    /// Carry the last known value forward.
+    Forward,
+    /// Interpolate linearly between the neighbouring known years.
+    Linear,
+}
+
+/// Fills the years missing between the first and last year of `values` and
+/// returns them. With [`Fill::None`] any gap is an error listing the years.
+pub fn fill_gaps(values: &mut BTreeMap<i32, f64>, fill: Fill) -> Result<Vec<i32>, String> {
+    let known: Vec<(i32, f64)> = values.iter().map(|(&y, &v)| (y, v)).collect();
+    let mut filled = Vec::new();
+
+    for pair in known.windows(2) {
+        let (from, from_value) = pair[0];
+        let (to, to_value) = pair[1];
+        for year in (from + 1)..to {
+            let value = match fill {
+                Fill::None | Fill::Forward => from_value,
+                Fill::Linear => {
+                    let t = (year - from) as f64 / (to - from) as f64;
+                    from_value + (to_value - from_value) * t
+                }
+            };
+            if fill != Fill::None {
+                values.insert(year, value);
+            }
+            filled.push(year);
+        }
+    }
+
+    if fill == Fill::None && !filled.is_empty() {
+        let years: Vec<String> = filled.iter().map(|y| y.to_string()).collect();
+        return Err(format!("missing years {}", years.join(", ")));
+    }
+    Ok(filled)
+}
+
+/// The first and last year covered by all of `series`, or `None` when they
+/// do not overlap. Gaps inside the range are not checked.
+pub fn common_years<'a>(
+    series: impl IntoIterator<Item = &'a BTreeMap<i32, f64>>,
+) -> Option<(i32, i32)> {
+    let mut range: Option<(i32, i32)> = None;
+    for by_year in series {
+        let (&first, _) = by_year.first_key_value()?;
+        let (&last, _) = by_year.last_key_value()?;
+        range = Some(match range {
+            Some((from, to)) => (from.max(first), to.min(last)),
+            None => (first, last),
+        });
+    }
+    range.filter(|(from, to)| from <= to)
+}
+
+/// Years in `window` that `a` has and `b` lacks, and the other way around.
+pub fn coverage_gaps(
+    a: &BTreeMap<i32, f64>,
+    b: &BTreeMap<i32, f64>,
+    window: (i32, i32),
+) -> (Vec<i32>, Vec<i32>) {
+    let only_in = |x: &BTreeMap<i32, f64>, y: &BTreeMap<i32, f64>| {
+        (window.0..=window.1)
+            .filter(|year| x.contains_key(year) && !y.contains_key(year))
+            .collect()
+    };
+    (only_in(a, b), only_in(b, a))
+}
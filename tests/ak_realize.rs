@@ -0,0 +1,27 @@
+mod common;
+
+use kf_vs_ak::{simulate, AkRealize, SimulationParams};
+
+fn aktiekonto_after_doublings(ak_realize: AkRealize) -> f64 {
+    let records = common::records([(2000, 100.0), (2001, 200.0), (2002, 400.0)], 0.0, 0.0);
+    let params = SimulationParams {
+        capital_gains_rate: 0.5,
+        ak_realize,
+        ..SimulationParams::default()
+    };
+    simulate(&records, &[2], (2000, 2002), &params).unwrap()[&2][0].aktiekonto
+}
+
+#[test]
+fn annual_realization_taxes_every_year() {
+    // 1 -> 2, taxed to 1.5; -> 3, taxed to 2.25.
+    let annual = aktiekonto_after_doublings(AkRealize::Annual);
+    assert!((annual - 2.25).abs() < 1e-12, "{annual}");
+}
+
+#[test]
+fn selling_at_the_end_defers_the_tax() {
+    // 1 -> 4, with the gain of 3 taxed once.
+    let sell = aktiekonto_after_doublings(AkRealize::Sell);
+    assert!((sell - 2.5).abs() < 1e-12, "{sell}");
+}
@@ -0,0 +1,45 @@
+mod common;
+
+/// Runs the golden fixtures with amounts in SEK, with `args` after them.
+fn run_sek(args: &[&str]) -> String {
+    let amounts = [
+        "--initial-capital",
+        "100000",
+        "--currency-label",
+        "SEK",
+        "--group-thousands",
+    ];
+    common::stdout(common::run_golden(&[&amounts[..], args].concat()))
+}
+
+#[test]
+fn winner_shows_the_amount() {
+    let stdout = run_sek(&["--lengths", "2", "--winner-only"]);
+    assert!(
+        stdout.contains("2 years: KF has the highest average final value, 98 445.23 SEK,"),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn markdown_shows_the_amounts() {
+    let stdout = run_sek(&["--lengths", "2,3", "--format", "markdown"]);
+    assert!(
+        stdout.contains("| 2015 | 147 640.00 SEK | 158 956.20 SEK | +7.66% |"),
+        "{stdout}"
+    );
+    let overview = stdout.split("### All lengths").nth(1).expect(&stdout);
+    assert!(overview.contains(" SEK | "), "{overview}");
+}
+
+#[test]
+fn switches_show_the_amounts() {
+    let stdout = run_sek(&["--lengths", "3", "--optimize-switch"]);
+    assert!(
+        stdout.contains(
+            "2015:     switch after 0 years    148 306.13 SEK    \
+             (AK only 139 700.00 SEK    KF only 148 306.13 SEK)"
+        ),
+        "{stdout}"
+    );
+}
@@ -0,0 +1,68 @@
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+
+fn run(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_kf_vs_ak"))
+        .current_dir(dir)
+        .args([
+            "--quiet",
+            "--slr",
+            "slr.csv",
+            "--lengths",
+            "2,3",
+            "--format",
+            "csv",
+        ])
+        .args(args)
+        .output()
+        .expect("failed to run kf_vs_ak")
+}
+
+#[test]
+fn appended_files_are_merged_with_omxs30() {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let dir = std::env::temp_dir().join(format!("kf_vs_ak_append_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::copy(fixtures.join("golden_slr.csv"), dir.join("slr.csv")).unwrap();
+    let omxs30 = fs::read_to_string(fixtures.join("golden_omxs30.txt")).unwrap();
+    let lines: Vec<&str> = omxs30.lines().collect();
+    fs::write(dir.join("all.txt"), &omxs30).unwrap();
+    fs::write(dir.join("late.txt"), lines[..4].join("\n")).unwrap();
+    fs::write(dir.join("early.txt"), lines[3..].join("\n")).unwrap();
+
+    let whole = run(&dir, &["--omxs30", "all.txt"]);
+    assert!(
+        whole.status.success(),
+        "{}",
+        String::from_utf8_lossy(&whole.stderr)
+    );
+    // The files share one date, which is kept once.
+    let merged = run(
+        &dir,
+        &["--omxs30", "late.txt", "--append-data", "early.txt"],
+    );
+    assert!(
+        merged.status.success(),
+        "{}",
+        String::from_utf8_lossy(&merged.stderr)
+    );
+    assert_eq!(merged.stdout, whole.stdout);
+
+    let strict = run(
+        &dir,
+        &[
+            "--omxs30",
+            "late.txt",
+            "--append-data",
+            "early.txt",
+            "--on-duplicate",
+            "error",
+        ],
+    );
+    assert!(!strict.status.success());
+    let stderr = String::from_utf8_lossy(&strict.stderr);
+    assert!(stderr.contains("--on-duplicate"), "{stderr}");
+
+    fs::remove_dir_all(&dir).unwrap();
+}
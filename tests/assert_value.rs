@@ -0,0 +1,45 @@
+mod common;
+
+use std::process::Output;
+
+fn run(args: &[&str]) -> Output {
+    common::run_golden(&[&["--quiet", "--lengths", "2"], args].concat())
+}
+
+#[test]
+fn matching_values_pass() {
+    let output = run(&[
+        "--assert-value",
+        "2021-12-30=95.00",
+        "--assert-value",
+        "2015-12-30=100.001",
+    ]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn every_mismatch_is_reported() {
+    let output = run(&[
+        "--assert-value",
+        "2021-12-30=96",
+        "--assert-value",
+        "2020-12-30=100",
+        "--assert-value",
+        "2020-06-30=100",
+    ]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("2021-12-30: expected 96, got 95"),
+        "{stderr}"
+    );
+    assert!(
+        stderr.contains("2020-06-30: expected 100, got no value"),
+        "{stderr}"
+    );
+    assert!(!stderr.contains("2020-12-30"), "{stderr}");
+}
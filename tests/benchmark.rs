@@ -0,0 +1,35 @@
+mod common;
+
+use kf_vs_ak::{simulate, Benchmark, BenchmarkTax, Record, SimulationParams};
+use std::collections::BTreeMap;
+
+fn records() -> BTreeMap<i32, Record> {
+    common::flat_records(2000..=2002, 0.01, 0.0)
+}
+
+fn benchmark_after(length: i32, tax: BenchmarkTax) -> f64 {
+    let params = SimulationParams {
+        benchmark: Some(Benchmark { rate: 10.0, tax }),
+        ..SimulationParams::default()
+    };
+    let series = simulate(&records(), &[length], (2000, 2002), &params).unwrap();
+    series[&length][0].benchmark.unwrap()
+}
+
+#[test]
+fn untaxed_benchmark_compounds_at_its_rate() {
+    let value = benchmark_after(2, BenchmarkTax::None);
+    assert!((value - 1.21).abs() < 1e-12, "{value}");
+}
+
+#[test]
+fn schablon_benchmark_is_taxed_on_the_start_value() {
+    let value = benchmark_after(1, BenchmarkTax::Schablon);
+    assert!((value - 1.09).abs() < 1e-12, "{value}");
+}
+
+#[test]
+fn no_benchmark_by_default() {
+    let series = simulate(&records(), &[1], (2000, 2002), &SimulationParams::default()).unwrap();
+    assert_eq!(series[&1][0].benchmark, None);
+}
@@ -0,0 +1,18 @@
+use std::process::Command;
+
+#[test]
+fn reads_files_with_bom_and_crlf() {
+    let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+    let output = Command::new(env!("CARGO_BIN_EXE_kf_vs_ak"))
+        .args(["--omxs30", &format!("{fixtures}/omxs30_bom_crlf.txt")])
+        .args(["--slr", &format!("{fixtures}/slr_bom_crlf.csv")])
+        .args(["--from-year", "2018", "--to-year", "2023", "--lengths", "3"])
+        .output()
+        .expect("failed to run kf_vs_ak");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{stderr}");
+    assert!(!stderr.contains("skipped"), "{stderr}");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("2018:     1.21    1.25"), "{stdout}");
+}
@@ -0,0 +1,49 @@
+mod common;
+
+use kf_vs_ak::{bootstrap_mean, mean, run, RunConfig, SimulationParams};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+#[test]
+fn interval_brackets_the_mean() {
+    let values = [1.0, 4.0, -2.0, 3.0, 0.5, 2.0];
+    let interval = bootstrap_mean(&values, 2000, 90.0, &mut StdRng::seed_from_u64(7)).unwrap();
+
+    let mean = mean(&values).unwrap();
+    assert!(
+        interval.lower < mean && mean < interval.upper,
+        "{interval:?}"
+    );
+    assert!(
+        interval.lower >= -2.0 && interval.upper <= 4.0,
+        "{interval:?}"
+    );
+    assert_eq!(interval.level, 90.0);
+    // A constant has no spread to resample.
+    let constant = bootstrap_mean(&[2.0; 4], 100, 90.0, &mut StdRng::seed_from_u64(7)).unwrap();
+    assert_eq!((constant.lower, constant.upper), (2.0, 2.0));
+    assert_eq!(
+        bootstrap_mean(&[], 100, 90.0, &mut StdRng::seed_from_u64(7)),
+        None
+    );
+}
+
+#[test]
+fn same_seed_gives_the_same_interval() {
+    let omxs30 = [100.0, 130.0, 90.0, 120.0, 110.0, 140.0];
+    let records = common::records((2000..).zip(omxs30), 0.01, 2.0);
+    let config = RunConfig {
+        lengths: vec![2],
+        range: (2000, 2005),
+        params: SimulationParams::default(),
+    };
+    let interval = |seed| {
+        let mut results = run(&config, &records).unwrap();
+        assert_eq!(results.summaries[&2].kf_difference_interval, None);
+        results.bootstrap(500, &mut StdRng::seed_from_u64(seed));
+        results.summaries[&2].kf_difference_interval.unwrap()
+    };
+
+    assert_eq!(interval(1), interval(1));
+    assert_ne!(interval(1), interval(2));
+}
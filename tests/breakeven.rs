@@ -0,0 +1,55 @@
+mod common;
+
+use kf_vs_ak::{find_breakeven, LengthAverages};
+
+#[test]
+fn breakeven_is_the_first_length_kf_leads() {
+    let averages = [(1, 1.1, 1.0), (2, 1.2, 1.25), (3, 1.3, 1.2)].map(
+        |(length, aktiekonto, kapitalförsäkring)| LengthAverages {
+            length,
+            aktiekonto,
+            kapitalförsäkring,
+        },
+    );
+    assert_eq!(find_breakeven(&averages), Some(2));
+    assert_eq!(find_breakeven(&averages[..1]), None);
+}
+
+fn run(format: &str) -> String {
+    common::stdout(common::run_golden(&[
+        "--find-breakeven",
+        "--format",
+        format,
+    ]))
+}
+
+#[test]
+fn every_format_reports_the_breakeven() {
+    let text = run("text");
+    assert!(
+        text.starts_with("1 years averages:    0.99    1.00\n"),
+        "{text}"
+    );
+    assert!(
+        text.ends_with("KF beats AK on average from 1 years\n"),
+        "{text}"
+    );
+
+    let csv = run("csv");
+    assert!(
+        csv.starts_with("length,aktiekonto,kapitalforsakring,kf_ahead\n1,"),
+        "{csv}"
+    );
+    assert!(csv.contains(",false\n"), "{csv}");
+
+    let json: serde_json::Value = serde_json::from_str(&run("json")).unwrap();
+    assert_eq!(json["breakeven"], 1);
+    assert_eq!(json["averages"].as_array().unwrap().len(), 6);
+
+    let markdown = run("markdown");
+    assert!(markdown.contains("| 2 years | 0.96 | 0.98 |"), "{markdown}");
+    assert!(
+        markdown.contains("KF beats AK on average from 1 years"),
+        "{markdown}"
+    );
+}
@@ -0,0 +1,58 @@
+use std::fs::{self, File};
+use std::path::Path;
+use std::process::{Command, Output};
+use std::time::{Duration, SystemTime};
+
+fn run(dir: &Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_kf_vs_ak"))
+        .current_dir(dir)
+        .args([
+            "--omxs30",
+            "omxs30.txt",
+            "--slr",
+            "slr.csv",
+            "--cache",
+            "inputs.bin",
+        ])
+        .args(["--from-year", "2018", "--to-year", "2023", "--lengths", "3"])
+        .arg("--verbose")
+        .output()
+        .expect("failed to run kf_vs_ak")
+}
+
+#[test]
+fn cache_is_reused_until_an_input_changes() {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let dir = std::env::temp_dir().join(format!("kf_vs_ak_cache_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::copy(fixtures.join("omxs30_bom_crlf.txt"), dir.join("omxs30.txt")).unwrap();
+    fs::copy(fixtures.join("slr_bom_crlf.csv"), dir.join("slr.csv")).unwrap();
+
+    let first = run(&dir);
+    assert!(
+        first.status.success(),
+        "{}",
+        String::from_utf8_lossy(&first.stderr)
+    );
+    assert!(dir.join("inputs.bin").exists());
+
+    let second = run(&dir);
+    let stderr = String::from_utf8_lossy(&second.stderr);
+    assert!(stderr.contains("read the inputs from --cache"), "{stderr}");
+    assert_eq!(first.stdout, second.stdout);
+
+    // An input modified after the cache was written is parsed again.
+    let later = SystemTime::now() + Duration::from_secs(60);
+    File::options()
+        .append(true)
+        .open(dir.join("slr.csv"))
+        .unwrap()
+        .set_modified(later)
+        .unwrap();
+    let third = run(&dir);
+    let stderr = String::from_utf8_lossy(&third.stderr);
+    assert!(!stderr.contains("read the inputs from --cache"), "{stderr}");
+    assert_eq!(first.stdout, third.stdout);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,65 @@
+//! Helpers shared by the integration tests. Each test file uses only some of
+//! them.
+#![allow(dead_code)]
+
+use kf_vs_ak::Record;
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+use std::process::{Command, Output};
+
+/// The index fixture behind the golden files, 2015 to 2021.
+pub const OMXS30: &str = "tests/fixtures/golden_omxs30.txt";
+/// The SLR fixture behind the golden files.
+pub const SLR: &str = "tests/fixtures/golden_slr.csv";
+
+/// Records with the given index values and the same schablon tax and SLR
+/// every year.
+pub fn records(
+    omxs30: impl IntoIterator<Item = (i32, f64)>,
+    avkastningsskatt: f64,
+    slr: f64,
+) -> BTreeMap<i32, Record> {
+    omxs30
+        .into_iter()
+        .map(|(year, omxs30)| {
+            let record = Record {
+                avkastningsskatt,
+                omxs30,
+                slr,
+            };
+            (year, record)
+        })
+        .collect()
+}
+
+/// Records with an index that stays at 100.
+pub fn flat_records(
+    years: RangeInclusive<i32>,
+    avkastningsskatt: f64,
+    slr: f64,
+) -> BTreeMap<i32, Record> {
+    records(years.map(|year| (year, 100.0)), avkastningsskatt, slr)
+}
+
+/// Runs the binary with `args`.
+pub fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_kf_vs_ak"))
+        .args(args)
+        .output()
+        .expect("failed to run kf_vs_ak")
+}
+
+/// Runs the binary on the golden fixtures, with `args` after them.
+pub fn run_golden(args: &[&str]) -> Output {
+    run(&[&["--omxs30", OMXS30, "--slr", SLR], args].concat())
+}
+
+/// The output of a run that must succeed, showing its stderr if it did not.
+pub fn stdout(output: Output) -> String {
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
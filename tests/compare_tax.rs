@@ -0,0 +1,53 @@
+mod common;
+
+use std::fs;
+use std::process::Output;
+
+fn run(args: &[&str]) -> Output {
+    common::run_golden(&[&["--quiet", "--lengths", "2,3"], args].concat())
+}
+
+#[test]
+fn one_row_of_win_rates_per_regime() {
+    let config = std::env::temp_dir().join(format!("kf_vs_ak_regimes_{}.toml", std::process::id()));
+    fs::write(
+        &config,
+        "[tax_regimes.untaxed]\ntax_base_rate = 0.0\n\n[tax_regimes.heavy]\ntax_base_rate = 1.0\n",
+    )
+    .unwrap();
+    let config = config.to_str().unwrap();
+
+    let output = run(&["--config", config, "--compare-tax", "current,untaxed,heavy"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let lines: Vec<&str> = stdout.lines().filter(|l| !l.is_empty()).collect();
+    assert_eq!(
+        lines[0].split_whitespace().collect::<Vec<_>>(),
+        ["Tax", "regime", "2", "years", "3", "years"]
+    );
+    let rates = |line: &str| -> Vec<f64> {
+        line.split_whitespace()
+            .skip(1)
+            .map(|rate| rate.trim_end_matches('%').parse().unwrap())
+            .collect()
+    };
+    let names: Vec<&str> = lines[1..]
+        .iter()
+        .map(|l| l.split_whitespace().next().unwrap())
+        .collect();
+    assert_eq!(names, ["current", "untaxed", "heavy"]);
+    // A higher avkastningsskatt never lets KF win more often.
+    for i in 0..2 {
+        assert!(rates(lines[3])[i] <= rates(lines[1])[i], "{stdout}");
+        assert!(rates(lines[1])[i] <= rates(lines[2])[i], "{stdout}");
+    }
+    assert!(rates(lines[3])[0] < rates(lines[2])[0], "{stdout}");
+
+    let output = run(&["--config", config, "--compare-tax", "missing"]);
+    assert!(!output.status.success());
+    fs::remove_file(config).unwrap();
+}
@@ -0,0 +1,17 @@
+mod common;
+
+use std::fs;
+
+#[test]
+fn bzip2_inputs_are_refused() {
+    let path = std::env::temp_dir().join(format!("kf_vs_ak_{}.txt.bz2", std::process::id()));
+    fs::write(&path, b"BZh91AY&SY").unwrap();
+    let output = common::run(&["--omxs30", path.to_str().unwrap(), "--slr", common::SLR]);
+    fs::remove_file(&path).unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("is bzip2-compressed, which is not supported"),
+        "{stderr}"
+    );
+}
@@ -0,0 +1,24 @@
+mod common;
+
+use std::fs;
+
+#[test]
+fn empty_lengths_are_an_error() {
+    let config = std::env::temp_dir().join(format!(
+        "kf_vs_ak_empty_lengths_{}.toml",
+        std::process::id()
+    ));
+    fs::write(&config, "lengths = []\n").unwrap();
+    let plot = std::env::temp_dir().join(format!("kf_vs_ak_empty_{}.svg", std::process::id()));
+    for extra in [&[][..], &["--plot", plot.to_str().unwrap()][..]] {
+        let output = common::run_golden(&[&["--config", config.to_str().unwrap()], extra].concat());
+        assert!(!output.status.success());
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(
+            stderr.contains("--lengths must list at least one holding length"),
+            "{stderr}"
+        );
+    }
+    fs::remove_file(&config).unwrap();
+    assert!(!plot.exists());
+}
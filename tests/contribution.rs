@@ -0,0 +1,39 @@
+mod common;
+
+use kf_vs_ak::{simulate, summarize, ContributionInterval, ContributionPlan, SimulationParams};
+
+#[test]
+fn contributions_have_an_irr_but_no_cagr() {
+    let records = common::records([(2000, 100.0), (2001, 120.0), (2002, 90.0)], 0.01, 2.0);
+    let params = SimulationParams {
+        contribution: Some(ContributionPlan {
+            amount: 1000.0,
+            interval: ContributionInterval::Yearly,
+        }),
+        ..SimulationParams::default()
+    };
+    let series = simulate(&records, &[2], (2000, 2002), &params).unwrap();
+    let entry = &series[&2][0];
+    assert_eq!(entry.cagr(entry.kapitalförsäkring, 2), None);
+    assert!(entry.kapitalförsäkring_irr.is_some());
+
+    let summary = summarize(2, &series[&2]);
+    assert_eq!(summary.kapitalförsäkring.mean_cagr, None);
+    assert!(summary.kapitalförsäkring.mean_irr.is_some());
+}
+
+#[test]
+fn contribution_output_leaves_out_the_cagr() {
+    let stdout = common::stdout(common::run_golden(&[
+        "--contribution",
+        "1000",
+        "--lengths",
+        "3",
+    ]));
+    assert!(stdout.contains("CAGR -    -    -"), "{stdout}");
+    assert!(
+        stdout.contains("3 years average CAGR:    -    -    -"),
+        "{stdout}"
+    );
+    assert!(stdout.contains("3 years average IRR:"), "{stdout}");
+}
@@ -0,0 +1,38 @@
+mod common;
+
+use std::fs;
+use std::process::Output;
+
+fn run(slr: &str, args: &[&str]) -> Output {
+    let inputs = ["--omxs30", common::OMXS30, "--slr", slr];
+    let range = ["--lengths", "2", "--from-year", "2015", "--to-year", "2021"];
+    common::run(&[&inputs[..], &range, args].concat())
+}
+
+#[test]
+fn years_without_slr_warn_or_fail_with_strict() {
+    let slr = fs::read_to_string(common::SLR).unwrap();
+    let short: String = slr
+        .lines()
+        .filter(|line| !line.starts_with("2020") && !line.starts_with("2021"))
+        .map(|line| format!("{line}\n"))
+        .collect();
+    let path = std::env::temp_dir().join(format!("kf_vs_ak_coverage_{}.csv", std::process::id()));
+    fs::write(&path, short).unwrap();
+    let path = path.to_str().unwrap();
+
+    let output = run(path, &[]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{stderr}");
+    assert!(stderr.contains("has no SLR for 2020, 2021"), "{stderr}");
+    assert!(stderr.contains("overlap in 2015..=2019"), "{stderr}");
+
+    let output = run(path, &["--strict"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("--strict: "), "{stderr}");
+
+    let output = run(common::SLR, &["--strict"]);
+    assert!(output.status.success());
+    fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,52 @@
+mod common;
+
+use std::process::Command;
+
+fn run(args: &[&str]) -> String {
+    let csv = ["--demo", "--quiet", "--format", "csv", "--lengths", "5"];
+    common::stdout(common::run(&[&csv[..], args].concat()))
+}
+
+#[test]
+fn tab_delimiter_writes_tsv() {
+    let stdout = run(&["--delimiter", "tab"]);
+    assert!(
+        stdout.starts_with(
+            "length\tstart_year\taktiekonto\tkapitalforsakring\tisk\tkapitalforsakring_tax\n"
+        ),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn fields_containing_the_delimiter_are_quoted() {
+    let stdout = run(&["--delimiter", ";", "--dump-combined"]);
+    assert!(
+        stdout.starts_with("year;omxs30;slr;avkastningsskatt\n"),
+        "{stdout}"
+    );
+
+    let demo = concat!(env!("CARGO_MANIFEST_DIR"), "/data/demo_omxs30.txt");
+    let output = Command::new(env!("CARGO_BIN_EXE_kf_vs_ak"))
+        .args(["--index", &format!("a;b={demo}")])
+        .args([
+            "--slr",
+            concat!(env!("CARGO_MANIFEST_DIR"), "/data/demo_slr.csv"),
+        ])
+        .args([
+            "--quiet",
+            "--format",
+            "csv",
+            "--delimiter",
+            ";",
+            "--lengths",
+            "5",
+        ])
+        .output()
+        .expect("failed to run kf_vs_ak");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.lines().nth(1).unwrap().starts_with("\"a;b\";5;"),
+        "{stdout}"
+    );
+}
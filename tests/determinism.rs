@@ -0,0 +1,15 @@
+use std::process::Command;
+
+fn run() -> Vec<u8> {
+    let output = Command::new(env!("CARGO_BIN_EXE_kf_vs_ak"))
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .output()
+        .expect("failed to run kf_vs_ak");
+    assert!(output.status.success());
+    output.stdout
+}
+
+#[test]
+fn repeated_runs_produce_identical_output() {
+    assert_eq!(run(), run());
+}
@@ -0,0 +1,20 @@
+mod common;
+
+use kf_vs_ak::{simulate, SimulationParams};
+
+#[test]
+fn aktiekonto_dividends_are_taxed_when_received() {
+    // A flat price index without schablon tax, so only the dividend counts.
+    let records = common::flat_records(2000..=2001, 0.0, 0.0);
+    let params = SimulationParams {
+        capital_gains_rate: 0.5,
+        dividend_yield: 10.0,
+        ..SimulationParams::default()
+    };
+
+    let series = simulate(&records, &[1], (2000, 2001), &params).unwrap();
+    let entry = &series[&1][0];
+    assert!((entry.kapitalförsäkring - 1.1).abs() < 1e-12, "{entry:?}");
+    assert!((entry.isk - 1.1).abs() < 1e-12, "{entry:?}");
+    assert!((entry.aktiekonto - 1.05).abs() < 1e-12, "{entry:?}");
+}
@@ -0,0 +1,56 @@
+mod common;
+
+use kf_vs_ak::{simulate, summarize, Record, SimulationParams};
+use std::collections::BTreeMap;
+
+fn records() -> BTreeMap<i32, Record> {
+    common::records([(2000, 100.0), (2001, 120.0), (2002, 90.0)], 0.01, 2.0)
+}
+
+#[test]
+fn drag_only_account_pays_only_its_fee() {
+    let params = SimulationParams {
+        drag_only_fee: Some(1.0),
+        ..SimulationParams::default()
+    };
+    let series = simulate(&records(), &[2], (2000, 2002), &params).unwrap();
+
+    let entry = &series[&2][0];
+    let expected = 1.2 * 0.99 * 0.75 * 0.99;
+    assert!(
+        (entry.drag_only.unwrap() - expected).abs() < 1e-12,
+        "{entry:?}"
+    );
+    assert!((entry.drag_only_max_drawdown.unwrap() - (1.0 - 0.75 * 0.99)).abs() < 1e-12);
+    let summary = summarize(2, &series[&2]);
+    assert_eq!(summary.drag_only.unwrap().mean, entry.drag_only);
+}
+
+#[test]
+fn drag_only_matches_kf_without_schablon_tax() {
+    let params = SimulationParams {
+        kf_fee: 0.5,
+        drag_only_fee: Some(0.5),
+        ..SimulationParams::default()
+    };
+    let untaxed: BTreeMap<i32, Record> = records()
+        .into_iter()
+        .map(|(year, record)| {
+            let record = Record {
+                avkastningsskatt: 0.0,
+                ..record
+            };
+            (year, record)
+        })
+        .collect();
+    let series = simulate(&untaxed, &[2], (2000, 2002), &params).unwrap();
+
+    let entry = &series[&2][0];
+    assert!((entry.drag_only.unwrap() - entry.kapitalförsäkring).abs() < 1e-12);
+}
+
+#[test]
+fn no_drag_only_account_by_default() {
+    let series = simulate(&records(), &[1], (2000, 2002), &SimulationParams::default()).unwrap();
+    assert_eq!(series[&1][0].drag_only, None);
+}
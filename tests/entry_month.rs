@@ -0,0 +1,56 @@
+mod common;
+
+use kf_vs_ak::{run, simulate, RunConfig, SimulationParams};
+use std::collections::BTreeMap;
+use std::fs;
+
+#[test]
+fn first_year_is_prorated_linearly() {
+    let records = common::records([(2000, 100.0), (2001, 120.0), (2002, 120.0)], 0.01, 0.0);
+    let params = SimulationParams {
+        entry_month: 7,
+        ..SimulationParams::default()
+    };
+
+    let series = simulate(&records, &[1, 2], (2000, 2002), &params).unwrap();
+    // Half of the 20% return and of the 1% tax on the unit invested.
+    let first = &series[&1][0];
+    assert!((first.kapitalförsäkring - 1.095).abs() < 1e-12, "{first:?}");
+    // Only the first simulated year of each start year is prorated.
+    let second_start = &series[&1][1];
+    assert!(
+        (second_start.kapitalförsäkring - 0.995).abs() < 1e-12,
+        "{second_start:?}"
+    );
+}
+
+#[test]
+fn out_of_range_months_are_errors() {
+    for entry_month in [0, 13] {
+        let config = RunConfig {
+            lengths: vec![1],
+            range: (2000, 2002),
+            params: SimulationParams {
+                entry_month,
+                ..SimulationParams::default()
+            },
+        };
+        let error = run(&config, &BTreeMap::new()).unwrap_err();
+        assert!(error.contains("entry_month must be 1 to 12"), "{error}");
+    }
+}
+
+#[test]
+fn config_entry_month_is_range_checked() {
+    let config =
+        std::env::temp_dir().join(format!("kf_vs_ak_entry_month_{}.toml", std::process::id()));
+    fs::write(&config, "entry_month = 20\n").unwrap();
+    let output = common::run_golden(&["--config", config.to_str().unwrap()]);
+    fs::remove_file(&config).unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("--entry-month must be 1 to 12, got 20"),
+        "{stderr}"
+    );
+}
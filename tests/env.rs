@@ -0,0 +1,34 @@
+use std::process::Command;
+
+#[test]
+fn input_paths_from_the_environment() {
+    let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+    let output = Command::new(env!("CARGO_BIN_EXE_kf_vs_ak"))
+        .env("KF_VS_AK_OMXS30", format!("{fixtures}/omxs30_bom_crlf.txt"))
+        .env("KF_VS_AK_SLR", format!("{fixtures}/slr_bom_crlf.csv"))
+        .args(["--from-year", "2018", "--to-year", "2023", "--lengths", "3"])
+        .arg("--verbose")
+        .output()
+        .expect("failed to run kf_vs_ak");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{stderr}");
+    assert!(stderr.contains("from $KF_VS_AK_SLR"), "{stderr}");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("2018:     1.21    1.25"), "{stdout}");
+}
+
+#[test]
+fn flags_take_precedence_over_the_environment() {
+    let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+    let output = Command::new(env!("CARGO_BIN_EXE_kf_vs_ak"))
+        .env("KF_VS_AK_OMXS30", "missing.txt")
+        .args(["--omxs30", &format!("{fixtures}/omxs30_bom_crlf.txt")])
+        .args(["--slr", &format!("{fixtures}/slr_bom_crlf.csv")])
+        .args(["--from-year", "2018", "--to-year", "2023", "--lengths", "3"])
+        .output()
+        .expect("failed to run kf_vs_ak");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{stderr}");
+}
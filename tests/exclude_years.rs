@@ -0,0 +1,49 @@
+mod common;
+
+use kf_vs_ak::{neutralize_years, simulate, Record, SimulationParams};
+use std::collections::BTreeMap;
+
+fn records() -> BTreeMap<i32, Record> {
+    common::records(
+        [(2000, 100.0), (2001, 50.0), (2002, 60.0), (2003, 90.0)],
+        0.01,
+        1.0,
+    )
+}
+
+#[test]
+fn neutralized_years_have_no_return_or_tax() {
+    let mut records = records();
+    neutralize_years(&mut records, &[2001]);
+    let values: Vec<f64> = records.values().map(|r| r.omxs30).collect();
+    assert_eq!(values, [100.0, 100.0, 120.0, 180.0]);
+    assert_eq!(records[&2001].avkastningsskatt, 0.0);
+    assert_eq!(records[&2002].avkastningsskatt, 0.01);
+}
+
+#[test]
+fn excluded_start_years_are_left_out() {
+    let params = SimulationParams {
+        excluded_start_years: vec![2001],
+        ..SimulationParams::default()
+    };
+    let series = simulate(&records(), &[1], (2000, 2003), &params).unwrap();
+    let start_years: Vec<i32> = series[&1].iter().map(|e| e.start_year).collect();
+    assert_eq!(start_years, [2000, 2002]);
+}
+
+#[test]
+fn the_output_names_the_excluded_years() {
+    let stdout = common::stdout(common::run_golden(&[
+        "--lengths",
+        "2",
+        "--exclude-years",
+        "2016,2018",
+    ]));
+    assert!(
+        stdout.starts_with("Start years left out: 2016, 2018\n"),
+        "{stdout}"
+    );
+    assert!(!stdout.contains("\n2016:"), "{stdout}");
+    assert!(stdout.contains("\n2017:"), "{stdout}");
+}
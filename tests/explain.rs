@@ -0,0 +1,44 @@
+mod common;
+
+use kf_vs_ak::{calculate_avkastningsskatt, explain_avkastningsskatt, TaxParams};
+
+#[test]
+fn explanation_matches_the_calculation() {
+    let params = TaxParams::default();
+    for (year, slr) in [(2010, 0.8), (2013, 0.5), (2016, 0.65), (2019, 0.15)] {
+        let explanation = explain_avkastningsskatt(year, slr, &params);
+        assert_eq!(
+            explanation.rate,
+            calculate_avkastningsskatt(year, slr, &params)
+        );
+    }
+    let floored = explain_avkastningsskatt(2019, 0.15, &params);
+    assert!(floored.floor_applied);
+    assert_eq!(floored.schablonintäkt, 1.25);
+    assert!(!explain_avkastningsskatt(2010, 0.8, &params)
+        .floor
+        .is_finite());
+}
+
+#[test]
+fn explain_prints_the_steps() {
+    let stdout = common::stdout(common::run_golden(&["--explain", "2019"]));
+    assert!(stdout.contains("SLR:                0.15%"), "{stdout}");
+    assert!(stdout.contains("so it applies"), "{stdout}");
+    assert!(
+        stdout.contains("max(0.15% + 1.00, 1.25%) = 1.25%"),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("= 0.3750% of the kapitalunderlag"),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn explain_needs_an_slr_for_the_year() {
+    let output = common::run_golden(&["--explain", "2005"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("has no SLR for 2005"), "{stderr}");
+}
@@ -0,0 +1,69 @@
+mod common;
+
+use kf_vs_ak::{simulate, summarize, write_series, Amounts, SimulationParams};
+
+#[test]
+fn lengths_with_few_start_years_are_warned_about() {
+    let output = common::run_golden(&["--lengths", "2,5"]);
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{stderr}");
+    assert!(
+        stderr.contains("warning: holding length 5 has only 2 start years in 2015..=2021"),
+        "{stderr}"
+    );
+    assert!(!stderr.contains("holding length 2 "), "{stderr}");
+    // The 5-year table and summary are left out.
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("2 years:"), "{stdout}");
+    assert!(!stdout.contains("5 years"), "{stdout}");
+}
+
+#[test]
+fn excluded_start_years_do_not_count() {
+    let output = common::run_golden(&["--lengths", "1,3", "--exclude-years", "2015,2016"]);
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{stderr}");
+    assert!(
+        stderr.contains(
+            "warning: holding length 3 has only 2 start years in 2015..=2021 after --exclude-years"
+        ),
+        "{stderr}"
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("1 years:"), "{stdout}");
+    assert!(!stdout.contains("3 years"), "{stdout}");
+}
+
+#[test]
+fn a_history_too_short_for_every_length_is_an_error() {
+    let output = common::run_golden(&["--from-year", "2017", "--lengths", "3"]);
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(
+        stderr.contains("has only 2 start years in 2017..=2021"),
+        "{stderr}"
+    );
+    assert!(
+        stderr.contains("no holding length has 3 or more start years"),
+        "{stderr}"
+    );
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn empty_series_has_no_table() {
+    let records = common::flat_records(2000..=2001, 0.01, 2.0);
+    let series = simulate(&records, &[5], (2000, 2001), &SimulationParams::default()).unwrap();
+    let entries = series.get(&5).map_or(&[][..], Vec::as_slice);
+
+    let mut out = Vec::new();
+    let summary = summarize(5, entries);
+    write_series(&mut out, 5, entries, &summary, 2, &Amounts::default()).unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "\n5 years: no start years\n"
+    );
+}
@@ -0,0 +1,76 @@
+use kf_vs_ak::{
+    combine_records, parse_omxs30_line, parse_slr_line, run, values_by_year, write_csv, write_text,
+    Amounts, NumberLocale, Results, RunConfig, SimulationParams, TaxParams, YearAnchor,
+    DEFAULT_PRECISION, SLR_DELIMITER, SLR_RATE_COLUMN,
+};
+
+// Prices rise from 2015 to 2017 and fall from 2018 to 2021, so the two year
+// holdings starting early are won by KF and the late ones by AK.
+const OMXS30: &str = include_str!("fixtures/golden_omxs30.txt");
+const SLR: &str = include_str!("fixtures/golden_slr.csv");
+const GOLDEN: &str = include_str!("fixtures/golden_output.txt");
+const GOLDEN_CSV: &str = include_str!("fixtures/golden_output.csv");
+const LENGTHS: [i32; 2] = [2, 3];
+
+fn golden_results() -> Results {
+    let omxs30: Vec<_> = OMXS30
+        .lines()
+        .map(|line| {
+            parse_omxs30_line(line, Some(NumberLocale::Sv))
+                .map(|r| (r.date, r.value))
+                .unwrap()
+        })
+        .collect();
+    let slr: Vec<_> = SLR
+        .lines()
+        .skip(1)
+        .map(|line| {
+            parse_slr_line(line, SLR_RATE_COLUMN, SLR_DELIMITER, Some(NumberLocale::Sv))
+                .map(|r| (r.date, r.value))
+                .unwrap()
+        })
+        .collect();
+    let records = combine_records(
+        &values_by_year(&omxs30, YearAnchor::Last),
+        &values_by_year(&slr, YearAnchor::Last),
+        &TaxParams::default(),
+    );
+    let config = RunConfig {
+        lengths: LENGTHS.to_vec(),
+        range: (2015, 2021),
+        params: SimulationParams::default(),
+    };
+    run(&config, &records).unwrap()
+}
+
+#[test]
+fn text_output_matches_golden_file() {
+    let mut out = Vec::new();
+    write_text(
+        &mut out,
+        &LENGTHS,
+        &golden_results(),
+        DEFAULT_PRECISION,
+        &Amounts::default(),
+    )
+    .unwrap();
+    let output = String::from_utf8(out).unwrap();
+    assert_eq!(output, GOLDEN, "output changed:\n{output}");
+}
+
+#[test]
+fn csv_output_matches_golden_file() {
+    let results = vec![("OMXS30".to_string(), golden_results())];
+    let mut out = Vec::new();
+    write_csv(&mut out, &LENGTHS, &results, false, ',').unwrap();
+    let output = String::from_utf8(out).unwrap();
+    assert_eq!(output, GOLDEN_CSV, "output changed:\n{output}");
+    // The overview of every length follows the start years.
+    let overview = output.split("\n\n").nth(1).expect(&output);
+    assert!(
+        overview.starts_with(
+            "length,mean_aktiekonto,mean_kapitalforsakring,kf_win_rate,mean_kf_difference\n2,"
+        ),
+        "{overview}"
+    );
+}
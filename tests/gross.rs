@@ -0,0 +1,55 @@
+mod common;
+
+fn run(args: &[&str]) -> std::process::Output {
+    common::run(&[&["--quiet", "--lengths", "1,3", "--slr", common::SLR], args].concat())
+}
+
+#[test]
+fn gross_index_is_read_instead_of_the_price_index() {
+    let price = run(&["--omxs30", common::OMXS30, "--format", "csv"]);
+    let gross = run(&[
+        "--omxs30",
+        "missing.txt",
+        "--gross",
+        common::OMXS30,
+        "--index-type",
+        "gross",
+        "--format",
+        "csv",
+    ]);
+
+    assert!(
+        gross.status.success(),
+        "{}",
+        String::from_utf8_lossy(&gross.stderr)
+    );
+    assert_eq!(gross.stdout, price.stdout);
+}
+
+#[test]
+fn gross_index_takes_no_dividend_yield() {
+    let output = run(&[
+        "--gross",
+        common::OMXS30,
+        "--index-type",
+        "gross",
+        "--dividend-yield",
+        "3",
+    ]);
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.starts_with("error: --dividend-yield "), "{stderr}");
+}
+
+#[test]
+fn gross_index_type_requires_a_file() {
+    let output = run(&["--index-type", "gross"]);
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("--index-type gross requires --gross"),
+        "{stderr}"
+    );
+}
@@ -0,0 +1,42 @@
+mod common;
+
+use kf_vs_ak::{run, RunConfig, SimulationParams};
+use std::collections::BTreeMap;
+
+#[test]
+fn real_cagr_does_not_depend_on_the_base_year() {
+    let records = common::records(
+        [(2000, 100.0), (2001, 130.0), (2002, 90.0), (2003, 140.0)],
+        0.01,
+        2.0,
+    );
+    let cpi: BTreeMap<i32, f64> = [(2000, 100.0), (2001, 104.0), (2002, 110.0), (2003, 113.0)]
+        .into_iter()
+        .collect();
+    for initial_capital in [None, Some(1000.0)] {
+        let config = RunConfig {
+            lengths: vec![2],
+            range: (2000, 2003),
+            params: SimulationParams {
+                initial_capital,
+                ..SimulationParams::default()
+            },
+        };
+        let deflated = |base_year| {
+            let mut results = run(&config, &records).unwrap();
+            results.deflate(&cpi, base_year).unwrap();
+            results
+        };
+        let own = deflated(None);
+        let based = deflated(Some(2003));
+        assert_ne!(own.entries(2)[0].aktiekonto, based.entries(2)[0].aktiekonto);
+        for (a, b) in own.entries(2).iter().zip(based.entries(2)) {
+            let (a, b) = (a.cagr(a.aktiekonto, 2), b.cagr(b.aktiekonto, 2));
+            assert!((a.unwrap() - b.unwrap()).abs() < 1e-12, "{a:?} {b:?}");
+        }
+        let mean_cagr = |results: &kf_vs_ak::Results| {
+            results.summaries[&2].kapitalförsäkring.mean_cagr.unwrap()
+        };
+        assert!((mean_cagr(&own) - mean_cagr(&based)).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,46 @@
+mod common;
+
+use kf_vs_ak::{simulate, summarize, Amounts, SimulationParams};
+
+#[test]
+fn values_scale_with_the_initial_capital() {
+    let records = common::records([(2000, 100.0), (2001, 120.0), (2002, 90.0)], 0.01, 2.0);
+    let unit = simulate(&records, &[2], (2000, 2002), &SimulationParams::default()).unwrap();
+    let params = SimulationParams {
+        initial_capital: Some(100_000.0),
+        ..SimulationParams::default()
+    };
+    let capital = simulate(&records, &[2], (2000, 2002), &params).unwrap();
+
+    let (unit, capital) = (&unit[&2][0], &capital[&2][0]);
+    assert!((capital.aktiekonto - unit.aktiekonto * 100_000.0).abs() < 1e-6);
+    assert!((capital.kapitalförsäkring - unit.kapitalförsäkring * 100_000.0).abs() < 1e-6);
+    assert!((capital.isk - unit.isk * 100_000.0).abs() < 1e-6);
+    assert_eq!(
+        capital.aktiekonto_max_drawdown,
+        unit.aktiekonto_max_drawdown
+    );
+
+    // Growth rates are of the multiplier, not of the amount.
+    let unit_summary = summarize(2, std::slice::from_ref(unit));
+    let capital_summary = summarize(2, std::slice::from_ref(capital));
+    let (a, b) = (
+        unit_summary.kapitalförsäkring.mean_cagr.unwrap(),
+        capital_summary.kapitalförsäkring.mean_cagr.unwrap(),
+    );
+    assert!((a - b).abs() < 1e-12, "{a} {b}");
+}
+
+#[test]
+fn amounts_group_thousands_and_take_a_label() {
+    let amounts = Amounts {
+        currency_label: Some("SEK".to_string()),
+        group_thousands: true,
+        ..Amounts::default()
+    };
+    assert_eq!(amounts.format(1234567.891, 2), "1 234 567.89 SEK");
+    assert_eq!(amounts.format(-999.5, 1), "-999.5 SEK");
+    assert_eq!(amounts.format_signed(12345.0, 0), "+12 345 SEK");
+    // Multipliers keep their plain rendering.
+    assert_eq!(Amounts::default().format(1234.5, 2), "1234.50");
+}
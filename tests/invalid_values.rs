@@ -0,0 +1,58 @@
+mod common;
+
+use kf_vs_ak::{
+    monte_carlo, optimize_switch, simulate, simulate_withdrawals, trajectory, SimulationParams,
+    YearStep,
+};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+#[test]
+fn start_years_through_a_nan_index_value_are_left_out() {
+    let records = common::records(
+        [
+            (2000, 100.0),
+            (2001, f64::NAN),
+            (2002, 100.0),
+            (2003, 110.0),
+        ],
+        0.0,
+        0.0,
+    );
+    let series = simulate(&records, &[1], (2000, 2003), &SimulationParams::default()).unwrap();
+    let start_years: Vec<i32> = series[&1].iter().map(|e| e.start_year).collect();
+    assert_eq!(start_years, [2002]);
+}
+
+#[test]
+fn a_missing_year_is_named_instead_of_panicking() {
+    let records = common::records([(2000, 100.0), (2001, 110.0), (2003, 120.0)], 0.0, 0.0);
+    let params = SimulationParams::default();
+    assert_eq!(
+        YearStep::of_year(&records, 2003).unwrap_err(),
+        "no record for 2002"
+    );
+    let range = (2000, 2003);
+    let missing = Err("no record for 2002".to_string());
+    assert_eq!(
+        simulate(&records, &[1], range, &params).map(|_| ()),
+        missing
+    );
+    assert_eq!(
+        trajectory(&records, 2000, 2003, &params).map(|_| ()),
+        missing
+    );
+    assert_eq!(
+        optimize_switch(&records, &[1], range, &params).map(|_| ()),
+        missing
+    );
+    assert_eq!(
+        simulate_withdrawals(&records, &[1], range, &params, 4.0).map(|_| ()),
+        missing
+    );
+    let mut rng = StdRng::seed_from_u64(1);
+    assert_eq!(
+        monte_carlo(&records, &[1], range, &params, 10, &mut rng, || {}).map(|_| ()),
+        missing
+    );
+}
@@ -0,0 +1,49 @@
+use std::process::Command;
+
+#[test]
+fn json_output_describes_the_run() {
+    let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+    let omxs30 = format!("{fixtures}/golden_omxs30.txt");
+    let output = Command::new(env!("CARGO_BIN_EXE_kf_vs_ak"))
+        .args(["--omxs30", &omxs30])
+        .args(["--slr", &format!("{fixtures}/golden_slr.csv")])
+        .args([
+            "--lengths",
+            "3",
+            "--format",
+            "json",
+            "--tax-base-rate",
+            "0.25",
+            "--kf-fee",
+            "0.3",
+            "--capital-gains-rate",
+            "0.3",
+        ])
+        .output()
+        .expect("failed to run kf_vs_ak");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let meta = &json["meta"];
+    assert_eq!(meta["version"], env!("CARGO_PKG_VERSION"));
+    assert_eq!(meta["from_year"], 2015);
+    assert_eq!(meta["to_year"], 2021);
+    assert_eq!(meta["tax"]["tax_base_rate"], 0.25);
+    let simulation = &meta["simulation"];
+    assert_eq!(simulation["kf_fee"], 0.3);
+    assert_eq!(simulation["capital_gains_rate"], 0.3);
+    assert_eq!(simulation["ak_realize"], "sell");
+    assert_eq!(simulation["entry_month"], 1);
+    assert!(meta["generated_at"].is_string());
+    let inputs = meta["inputs"].as_array().unwrap();
+    assert_eq!(inputs.len(), 2);
+    assert_eq!(inputs[0]["flag"], "--omxs30");
+    assert_eq!(inputs[0]["path"], omxs30.as_str());
+    assert!(inputs[0]["modified"].is_string());
+    assert_eq!(inputs[1]["flag"], "--slr");
+    assert!(json["results"]["3"]["entries"].is_array());
+}
@@ -0,0 +1,42 @@
+mod common;
+
+use kf_vs_ak::{run, Record, RunConfig, SimulationParams};
+use std::collections::BTreeMap;
+
+fn flat_records() -> BTreeMap<i32, Record> {
+    common::flat_records(2000..=2003, 0.01, 2.0)
+}
+
+#[test]
+fn kf_tax_is_what_the_schablon_took() {
+    let mut config = RunConfig {
+        lengths: vec![2],
+        range: (2000, 2003),
+        params: SimulationParams::default(),
+    };
+    let results = run(&config, &flat_records()).unwrap();
+    // Without returns or a fee, only the tax shrinks the kapitalförsäkring.
+    for e in results.entries(2) {
+        assert!((e.kapitalförsäkring_tax - (0.01 + 0.01 * 0.99)).abs() < 1e-12);
+        assert!((e.kapitalförsäkring + e.kapitalförsäkring_tax - 1.0).abs() < 1e-12);
+    }
+    let mean_tax = results.summaries[&2].mean_kapitalförsäkring_tax.unwrap();
+
+    config.params.initial_capital = Some(1000.0);
+    let results = run(&config, &flat_records()).unwrap();
+    let amount = results.summaries[&2].mean_kapitalförsäkring_tax.unwrap();
+    assert!((amount - mean_tax * 1000.0).abs() < 1e-9);
+}
+
+#[test]
+fn csv_has_a_kf_tax_column() {
+    let stdout = common::stdout(common::run_golden(&[
+        "--quiet",
+        "--lengths",
+        "2",
+        "--format",
+        "csv",
+    ]));
+    let header: Vec<&str> = stdout.lines().next().unwrap().split(',').collect();
+    assert!(header.contains(&"kapitalforsakring_tax"));
+}
@@ -0,0 +1,41 @@
+mod common;
+
+use std::fs;
+
+fn run(args: &[&str]) -> String {
+    common::stdout(common::run(args))
+}
+
+#[test]
+fn spreadsheet_floats_keep_three_decimals() {
+    // The cells of 2021 and 2020 are the floats 0.125 and 2.125.
+    let stdout = run(&[
+        "--omxs30",
+        common::OMXS30,
+        "--slr",
+        "tests/fixtures/slr.xlsx",
+        "--input-locale",
+        "sv",
+        "--dump-combined",
+    ]);
+    assert!(stdout.contains("\n2020    100    2.125    "), "{stdout}");
+    assert!(stdout.contains("\n2021    95    0.125    "), "{stdout}");
+}
+
+#[test]
+fn fetch_cache_is_read_with_a_decimal_point() {
+    let cache = std::env::temp_dir().join(format!("kf_vs_ak_fetch_{}.txt", std::process::id()));
+    fs::write(&cache, "2019-12-30\t985.125\n2020-12-30\t1000.5\n").unwrap();
+    let stdout = run(&[
+        "--fetch",
+        "--fetch-cache",
+        cache.to_str().unwrap(),
+        "--slr",
+        common::SLR,
+        "--input-locale",
+        "sv",
+        "--dump-combined",
+    ]);
+    fs::remove_file(&cache).unwrap();
+    assert!(stdout.contains("\n2019    985.125    "), "{stdout}");
+}
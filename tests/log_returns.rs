@@ -0,0 +1,30 @@
+mod common;
+
+use kf_vs_ak::{sample_std_dev, simulate, SimulationParams};
+
+#[test]
+fn log_returns_change_only_the_sharpe_ratio() {
+    let records = common::records([(2000, 100.0), (2001, 150.0), (2002, 120.0)], 0.0, 0.0);
+    let simple =
+        &simulate(&records, &[2], (2000, 2002), &SimulationParams::default()).unwrap()[&2][0];
+    let params = SimulationParams {
+        log_returns: true,
+        ..SimulationParams::default()
+    };
+    let log = &simulate(&records, &[2], (2000, 2002), &params).unwrap()[&2][0];
+
+    let logs = [1.5_f64.ln(), 0.8_f64.ln()];
+    let expected = (logs[0] + logs[1]) / 2.0 / sample_std_dev(&logs).unwrap();
+    let sharpe = log.kapitalförsäkring_sharpe.unwrap();
+    assert!((sharpe - expected).abs() < 1e-12, "{sharpe} vs {expected}");
+    assert_ne!(
+        simple.kapitalförsäkring_sharpe,
+        log.kapitalförsäkring_sharpe
+    );
+
+    assert_eq!(simple.kapitalförsäkring, log.kapitalförsäkring);
+    assert_eq!(
+        simple.kapitalförsäkring_volatility,
+        log.kapitalförsäkring_volatility
+    );
+}
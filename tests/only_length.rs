@@ -0,0 +1,26 @@
+mod common;
+
+use std::process::Output;
+
+fn run(args: &[&str]) -> Output {
+    common::run_golden(args)
+}
+
+#[test]
+fn only_length_matches_a_single_length() {
+    let only = run(&["--only-length", "2"]);
+    assert!(
+        only.status.success(),
+        "{}",
+        String::from_utf8_lossy(&only.stderr)
+    );
+    assert_eq!(only.stdout, run(&["--lengths", "2"]).stdout);
+}
+
+#[test]
+fn only_length_must_fit_the_data() {
+    let output = run(&["--only-length", "99"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("--only-length 99 does not fit"), "{stderr}");
+}
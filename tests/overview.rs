@@ -0,0 +1,28 @@
+mod common;
+
+#[test]
+fn markdown_ends_with_an_overview_of_all_lengths() {
+    let stdout = common::stdout(common::run_golden(&[
+        "--lengths",
+        "2,3",
+        "--format",
+        "markdown",
+    ]));
+    let overview = stdout.split("### All lengths").nth(1).expect(&stdout);
+    assert!(
+        overview.contains("| 2 years | 0.96 | 0.98 | 40.00% | +1.39% |"),
+        "{overview}"
+    );
+    assert!(
+        overview.contains("| 3 years | 0.89 | 0.91 | 25.00% | +0.54% |"),
+        "{overview}"
+    );
+}
+
+#[test]
+fn json_summaries_carry_the_win_rate() {
+    let output = common::run_golden(&["--lengths", "3", "--format", "json"]);
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["results"]["3"]["summary"]["kf_win_rate"], 0.25);
+}
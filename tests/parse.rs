@@ -0,0 +1,123 @@
+use chrono::NaiveDate;
+use kf_vs_ak::{
+    parse_number, parse_slr_line, parse_swedish_number, NumberLocale, ParseError, SLR_DELIMITER,
+    SLR_RATE_COLUMN,
+};
+
+fn parse(s: &str) -> f64 {
+    parse_swedish_number(s).unwrap_or_else(|e| panic!("{s:?}: {e}"))
+}
+
+#[test]
+fn swedish_format() {
+    assert_eq!(parse("1 234,56"), 1234.56);
+    assert_eq!(parse("2 141,23"), 2141.23);
+    assert_eq!(parse("2,45"), 2.45);
+    assert_eq!(parse("1\u{a0}234,5"), 1234.5);
+}
+
+#[test]
+fn plain_format() {
+    assert_eq!(parse("1234.56"), 1234.56);
+    assert_eq!(parse("263"), 263.0);
+    assert_eq!(parse("-0.5"), -0.5);
+}
+
+#[test]
+fn grouped_format() {
+    assert_eq!(parse("1,234"), 1234.0);
+    assert_eq!(parse("1,234.56"), 1234.56);
+    assert_eq!(parse("12,345,678"), 12345678.0);
+    assert_eq!(parse("1.234,56"), 1234.56);
+}
+
+#[test]
+fn scientific_notation() {
+    assert_eq!(parse("2,5e2"), 250.0);
+    assert_eq!(parse("1.5E3"), 1500.0);
+    assert_eq!(parse("-2,5e-2"), -0.025);
+    // The three digits after the comma are decimals, not a group.
+    assert_eq!(parse("1,234e3"), 1234.0);
+    let sv = Some(NumberLocale::Sv);
+    let en = Some(NumberLocale::En);
+    assert_eq!(parse_number("2,5e2", sv).unwrap(), 250.0);
+    assert_eq!(parse_number("1e3", sv).unwrap(), 1000.0);
+    assert_eq!(parse_number("2.5e+2", en).unwrap(), 250.0);
+    assert!(parse_swedish_number("2,5e").is_err());
+    assert!(parse_swedish_number("e3").is_err());
+}
+
+#[test]
+fn malformed_input_is_rejected() {
+    assert!(parse_swedish_number("").is_err());
+    assert!(parse_swedish_number("abc").is_err());
+    assert!(parse_swedish_number("1,23,4.5").is_err());
+    assert!(parse_swedish_number("1,2,3").is_err());
+    assert!(parse_swedish_number("12.34.5").is_err());
+}
+
+#[test]
+fn non_finite_values_are_rejected() {
+    for s in ["NaN", "inf", "-infinity", "1e999"] {
+        let error = parse_swedish_number(s).unwrap_err();
+        assert_eq!(error.reason, "not a finite number", "{s:?}");
+        assert!(parse_number(s, Some(NumberLocale::En)).is_err(), "{s:?}");
+        assert!(parse_number(s, Some(NumberLocale::Sv)).is_err(), "{s:?}");
+    }
+    let error = parse_number("1e999", Some(NumberLocale::Sv)).unwrap_err();
+    assert_eq!(error.reason, "not a finite number");
+}
+
+#[test]
+fn declared_locale_decides_ambiguous_commas() {
+    let sv = Some(NumberLocale::Sv);
+    let en = Some(NumberLocale::En);
+    assert_eq!(parse_number("1,234", sv).unwrap(), 1.234);
+    assert_eq!(parse_number("1,234", en).unwrap(), 1234.0);
+    assert_eq!(parse_number("1 234,56", sv).unwrap(), 1234.56);
+    assert_eq!(parse_number("1,234.56", en).unwrap(), 1234.56);
+    assert_eq!(parse_number("-0.5", en).unwrap(), -0.5);
+    assert_eq!(parse_number("263", sv).unwrap(), 263.0);
+}
+
+#[test]
+fn values_in_the_other_locale_are_rejected() {
+    let sv = Some(NumberLocale::Sv);
+    let en = Some(NumberLocale::En);
+    let error = parse_number("1234.56", sv).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "invalid number \"1234.56\": a point is not a decimal separator in sv numbers"
+    );
+    assert!(parse_number("2,45", en).is_err());
+    assert!(parse_number("1 234,56", en).is_err());
+    assert!(parse_number("1,2,3", sv).is_err());
+    assert!(parse_number("", sv).is_err());
+}
+
+#[test]
+fn quoted_slr_fields() {
+    let record =
+        parse_slr_line(r#""2023-12-29";"3,54";"3,10""#, SLR_RATE_COLUMN, ';', None).unwrap();
+    assert_eq!(record.date, NaiveDate::from_ymd_opt(2023, 12, 29).unwrap());
+    assert_eq!(record.value, 3.54);
+    // A quoted field may contain the delimiter.
+    let record =
+        parse_slr_line(r#""2023-12-29","3,54","3,10""#, SLR_RATE_COLUMN, ',', None).unwrap();
+    assert_eq!(record.value, 3.54);
+}
+
+#[test]
+fn unquoted_slr_fields() {
+    let record = parse_slr_line("2023-12-29;3,54;3,10", 3, SLR_DELIMITER, None).unwrap();
+    assert_eq!(record.value, 3.10);
+}
+
+#[test]
+fn slr_column_zero_is_missing() {
+    let result = parse_slr_line("2023-12-29;3,54", 0, SLR_DELIMITER, None);
+    assert!(matches!(
+        result,
+        Err(ParseError::MissingColumn { column: 0, .. })
+    ));
+}
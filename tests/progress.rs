@@ -0,0 +1,49 @@
+mod common;
+
+#[test]
+fn no_progress_when_stderr_is_not_a_terminal() {
+    let output = common::run_golden(&["--lengths", "2", "--monte-carlo", "50", "--seed", "1"]);
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!stderr.contains("Monte Carlo paths"), "{stderr}");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("Monte Carlo with 50 paths"), "{stdout}");
+}
+
+/// Runs the Monte Carlo on a pseudo-terminal from util-linux `script`, with
+/// `env` added to the environment, and returns what the terminal showed.
+#[cfg(target_os = "linux")]
+fn on_terminal(env: &[(&str, &str)]) -> Option<String> {
+    let command = format!(
+        "{} --omxs30 {} --slr {} --lengths 2 --monte-carlo 500 --seed 1 >/dev/null",
+        env!("CARGO_BIN_EXE_kf_vs_ak"),
+        common::OMXS30,
+        common::SLR
+    );
+    let output = std::process::Command::new("script")
+        .args(["-qec", &command, "/dev/null"])
+        .env_remove("CI")
+        .env("TERM", "xterm")
+        .envs(env.iter().copied())
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn progress_only_on_an_interactive_terminal() {
+    let Some(terminal) = on_terminal(&[]) else {
+        // No `script` to give the run a terminal.
+        return;
+    };
+    assert!(
+        terminal.contains("Monte Carlo paths: 500/500"),
+        "{terminal}"
+    );
+    assert_eq!(on_terminal(&[("CI", "true")]).unwrap(), "");
+    assert_eq!(on_terminal(&[("TERM", "dumb")]).unwrap(), "");
+}
@@ -0,0 +1,21 @@
+mod common;
+
+#[test]
+fn quiet_leaves_only_the_results() {
+    let output = common::run(&["--demo", "--quiet", "--format", "csv", "--lengths", "5"]);
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{stderr}");
+    assert!(stderr.is_empty(), "{stderr}");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("length,start_year,"), "{stdout}");
+}
+
+#[test]
+fn errors_are_printed_without_quotes() {
+    let output = common::run(&["--demo", "--quiet", "--lengths", "0"]);
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.starts_with("error: holding length 0 "), "{stderr}");
+}
@@ -0,0 +1,31 @@
+mod common;
+
+use kf_vs_ak::{regime_split, simulate, RegimeWindow, SimulationParams};
+
+#[test]
+fn windows_follow_the_years_held() {
+    // A 2-year holding from 2015 covers 2016 and 2017.
+    assert_eq!(RegimeWindow::of(2015, 2, 2018), RegimeWindow::Before);
+    assert_eq!(RegimeWindow::of(2016, 2, 2018), RegimeWindow::Straddling);
+    assert_eq!(RegimeWindow::of(2017, 2, 2018), RegimeWindow::After);
+}
+
+#[test]
+fn every_start_year_is_in_one_group() {
+    let omxs30 = (2010..=2020).map(|year| (year, 100.0 + (year - 2010) as f64 * 10.0));
+    let records = common::records(omxs30, 0.01, 1.0);
+    let series = simulate(&records, &[3], (2010, 2020), &SimulationParams::default()).unwrap();
+    let groups = regime_split(&series, 2016);
+    let counts: Vec<(RegimeWindow, usize)> = groups.iter().map(|g| (g.window, g.count)).collect();
+    // Start years 2010 to 2012 end by 2015, 2013 to 2014 straddle and
+    // 2015 to 2017 begin in 2016 or later.
+    assert_eq!(
+        counts,
+        [
+            (RegimeWindow::Before, 3),
+            (RegimeWindow::Straddling, 2),
+            (RegimeWindow::After, 3)
+        ]
+    );
+    assert!(groups.iter().all(|g| g.mean_kf_difference.is_some()));
+}
@@ -0,0 +1,36 @@
+mod common;
+
+use kf_vs_ak::{run, RunConfig, SimulationParams};
+
+#[test]
+fn run_returns_the_summary_of_each_length() {
+    let records = common::records(
+        [(2000, 100.0), (2001, 150.0), (2002, 120.0), (2003, 130.0)],
+        0.01,
+        2.0,
+    );
+    let config = RunConfig {
+        lengths: vec![1, 5],
+        range: (2000, 2003),
+        params: SimulationParams::default(),
+    };
+
+    let results = run(&config, &records).unwrap();
+    let entries = results.entries(1);
+    assert_eq!(entries.len(), 3);
+    let kf_wins = entries
+        .iter()
+        .filter(|e| e.kapitalförsäkring > e.aktiekonto)
+        .count();
+    let summary = &results.summaries[&1];
+    assert_eq!(summary.kf_wins, kf_wins);
+    assert_eq!(summary.kf_win_rate, Some(kf_wins as f64 / 3.0));
+    let mut kf: Vec<f64> = entries.iter().map(|e| e.kapitalförsäkring).collect();
+    kf.sort_by(f64::total_cmp);
+    assert_eq!(summary.kapitalförsäkring.median, Some(kf[1]));
+
+    // A length longer than the range has a summary without start years.
+    assert!(results.entries(5).is_empty());
+    assert_eq!(results.summaries[&5].count, 0);
+    assert_eq!(results.summaries[&5].kf_win_rate, None);
+}
@@ -0,0 +1,59 @@
+mod common;
+
+use kf_vs_ak::{is_spreadsheet, parse_omxs30_line, sheet_lines};
+use std::path::Path;
+use std::process::Command;
+
+#[test]
+fn spreadsheet_rows_become_omxs30_lines() {
+    let path = Path::new(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/omxs30.xlsx"
+    ));
+    assert!(is_spreadsheet(path));
+    assert!(!is_spreadsheet(Path::new("omxs30.txt")));
+
+    let lines = sheet_lines(path, Some("Kurser"), 1, 3).unwrap();
+    assert_eq!(lines[0], "Datum\tStängning");
+    assert_eq!(lines[1], "2023-12-28\t2000");
+    let record = parse_omxs30_line(&lines[3], None).unwrap();
+    assert_eq!(record.date.to_string(), "2021-12-28");
+    assert_eq!(record.value, 1900.25);
+
+    assert!(sheet_lines(path, Some("Missing"), 1, 3).is_err());
+    assert!(sheet_lines(path, None, 0, 3).is_err());
+    assert!(sheet_lines(path, None, 1, 0).is_err());
+}
+
+#[test]
+fn spreadsheet_input_matches_the_text_file() {
+    let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+    let run = |omxs30: &str, extra: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_kf_vs_ak"))
+            .args(["--omxs30", &format!("{fixtures}/{omxs30}")])
+            .args(["--slr", &format!("{fixtures}/slr_bom_crlf.csv")])
+            .args(["--from-year", "2018", "--to-year", "2023", "--lengths", "3"])
+            .args(extra)
+            .output()
+            .expect("failed to run kf_vs_ak")
+    };
+
+    let text = run("omxs30_bom_crlf.txt", &[]);
+    let sheet = run("omxs30.xlsx", &["--value-column", "3"]);
+    let stderr = String::from_utf8_lossy(&sheet.stderr);
+    assert!(sheet.status.success(), "{stderr}");
+    // The header row is reported like any other line that is not a record.
+    assert!(stderr.contains("skipped 1 of 7 lines"), "{stderr}");
+    assert_eq!(text.stdout, sheet.stdout);
+}
+
+#[test]
+fn column_zero_is_rejected() {
+    let output = common::run(&["--demo", "--value-column", "0"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("must be 1 or higher, got 0"), "{stderr}");
+    let output = common::run(&["--demo", "--slr-column", "1"]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("must be 2 or higher, got 1"), "{stderr}");
+}
@@ -0,0 +1,39 @@
+mod common;
+
+use std::fs;
+use std::process::Output;
+
+fn run(args: &[&str]) -> Output {
+    let csv = ["--quiet", "--lengths", "2,3", "--format", "csv"];
+    common::run(&[&csv[..], &["--omxs30", common::OMXS30], args].concat())
+}
+
+#[test]
+fn override_replaces_every_years_slr() {
+    let path = std::env::temp_dir().join(format!("kf_vs_ak_slr_{}.csv", std::process::id()));
+    let lines: String = (2015..=2021)
+        .map(|year| format!("{year}-12-30;1,50;1,50\n"))
+        .collect();
+    fs::write(
+        &path,
+        format!("Datum;Räntesats %;Medelvärde hittills i år\n{lines}"),
+    )
+    .unwrap();
+
+    let from_file = run(&["--slr", path.to_str().unwrap()]);
+    assert!(
+        from_file.status.success(),
+        "{}",
+        String::from_utf8_lossy(&from_file.stderr)
+    );
+    // No SLR file is needed with the override.
+    let overridden = run(&["--slr-override", "1.5"]);
+    assert!(
+        overridden.status.success(),
+        "{}",
+        String::from_utf8_lossy(&overridden.stderr)
+    );
+    assert_eq!(overridden.stdout, from_file.stdout);
+
+    fs::remove_file(&path).unwrap();
+}
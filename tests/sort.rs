@@ -0,0 +1,55 @@
+mod common;
+
+use kf_vs_ak::{simulate, sort_entries, summarize, SimulationParams, SortKey};
+
+fn series() -> Vec<kf_vs_ak::SeriesEntry> {
+    let omxs30 = [
+        (2000, 100.0),
+        (2001, 130.0),
+        (2002, 90.0),
+        (2003, 120.0),
+        (2004, 125.0),
+    ];
+    let records = common::records(omxs30, 0.01, 2.0);
+    simulate(&records, &[1], (2000, 2004), &SimulationParams::default())
+        .unwrap()
+        .remove(&1)
+        .unwrap()
+}
+
+#[test]
+fn sorts_by_the_chosen_key() {
+    let mut entries = series();
+    sort_entries(&mut entries, SortKey::Ak, true);
+    let years: Vec<i32> = entries.iter().map(|e| e.start_year).collect();
+    assert_eq!(years, [2002, 2000, 2003, 2001]);
+
+    sort_entries(&mut entries, SortKey::Diff, false);
+    let diffs: Vec<f64> = entries.iter().map(|e| e.kf_difference_pct()).collect();
+    assert!(diffs.windows(2).all(|w| w[0] <= w[1]), "{diffs:?}");
+
+    sort_entries(&mut entries, SortKey::Year, false);
+    let years: Vec<i32> = entries.iter().map(|e| e.start_year).collect();
+    assert_eq!(years, [2000, 2001, 2002, 2003]);
+}
+
+#[test]
+fn sort_order_does_not_change_the_summary() {
+    let by_year = summarize(1, &series());
+    let mut sorted = series();
+    sort_entries(&mut sorted, SortKey::Kf, true);
+    let by_kf = summarize(1, &sorted);
+    // Only the order of the floating-point sums differs.
+    for (a, b) in [
+        (by_year.aktiekonto.mean, by_kf.aktiekonto.mean),
+        (by_year.mean_kf_difference, by_kf.mean_kf_difference),
+        (
+            by_year.kf_advantage_trend.map(|t| t.slope),
+            by_kf.kf_advantage_trend.map(|t| t.slope),
+        ),
+    ] {
+        let (a, b) = (a.unwrap(), b.unwrap());
+        assert!((a - b).abs() < 1e-12, "{a} {b}");
+    }
+    assert_eq!(by_year.kf_wins, by_kf.kf_wins);
+}
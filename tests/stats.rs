@@ -0,0 +1,58 @@
+use kf_vs_ak::{geometric_mean, histogram, irr, linear_regression};
+
+#[test]
+fn geometric_mean_of_multipliers() {
+    let mean = geometric_mean(&[2.0, 8.0]).unwrap();
+    assert!((mean - 4.0).abs() < 1e-12, "{mean}");
+}
+
+#[test]
+fn geometric_mean_rejects_non_positive_values() {
+    assert_eq!(geometric_mean(&[]), None);
+    assert_eq!(geometric_mean(&[1.5, 0.0]), None);
+    assert_eq!(geometric_mean(&[1.5, -1.0]), None);
+    assert_eq!(geometric_mean(&[1.5, f64::NAN]), None);
+}
+
+#[test]
+fn irr_of_a_single_deposit_is_its_growth_rate() {
+    let rate = irr(&[(0.0, -100.0), (2.0, 121.0)]).unwrap();
+    assert!((rate - 0.1).abs() < 1e-9, "{rate}");
+}
+
+#[test]
+fn irr_of_regular_deposits() {
+    // 100 at the start of each of two years, 231 at the end: 10% a year.
+    let rate = irr(&[(0.0, -100.0), (1.0, -100.0), (2.0, 231.0)]).unwrap();
+    assert!((rate - 0.1).abs() < 1e-9, "{rate}");
+}
+
+#[test]
+fn irr_without_a_root_is_none() {
+    assert_eq!(irr(&[(0.0, -100.0), (1.0, -100.0)]), None);
+}
+
+#[test]
+fn regression_of_points_on_a_line() {
+    let fit = linear_regression(&[(2000.0, 1.0), (2001.0, 3.0), (2002.0, 5.0)]).unwrap();
+    assert!((fit.slope - 2.0).abs() < 1e-9, "{fit:?}");
+    assert!((fit.intercept + 3999.0).abs() < 1e-6, "{fit:?}");
+    assert!((fit.r_squared - 1.0).abs() < 1e-12, "{fit:?}");
+}
+
+#[test]
+fn regression_needs_two_distinct_x_values() {
+    assert_eq!(linear_regression(&[(2000.0, 1.0)]), None);
+    assert_eq!(linear_regression(&[(2000.0, 1.0), (2000.0, 2.0)]), None);
+}
+
+#[test]
+fn histogram_includes_empty_buckets() {
+    let buckets = histogram(&[-3.0, 1.0, 4.9, 12.0], 5.0);
+    assert_eq!(
+        buckets,
+        vec![(-5.0, 1), (0.0, 2), (5.0, 0), (10.0, 1)],
+        "{buckets:?}"
+    );
+    assert!(histogram(&[], 5.0).is_empty());
+}
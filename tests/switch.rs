@@ -0,0 +1,44 @@
+mod common;
+
+use kf_vs_ak::{mean_switch_after, optimize_switch, simulate, Record, SimulationParams};
+use std::collections::BTreeMap;
+
+fn records() -> BTreeMap<i32, Record> {
+    let omxs30 = [
+        (2000, 100.0),
+        (2001, 80.0),
+        (2002, 110.0),
+        (2003, 150.0),
+        (2004, 140.0),
+    ];
+    common::records(omxs30, 0.0125, 2.0)
+}
+
+#[test]
+fn best_switch_beats_never_switching() {
+    let records = records();
+    let params = SimulationParams::default();
+    let switches = optimize_switch(&records, &[2, 4], (2000, 2004), &params).unwrap();
+    let series = simulate(&records, &[2, 4], (2000, 2004), &params).unwrap();
+
+    for length in [2, 4] {
+        for (switch, entry) in switches[&length].iter().zip(&series[&length]) {
+            assert_eq!(switch.start_year, entry.start_year);
+            assert!((switch.aktiekonto - entry.aktiekonto).abs() < 1e-12);
+            assert!((switch.kapitalförsäkring - entry.kapitalförsäkring).abs() < 1e-12);
+            assert!(switch.value >= switch.aktiekonto.max(switch.kapitalförsäkring));
+            assert!((0..=length).contains(&switch.switch_after));
+        }
+    }
+}
+
+#[test]
+fn switching_after_a_loss_is_tax_free() {
+    // Held through the fall, the AK has no gain to tax, so moving into the
+    // KF for the recovery beats both accounts held throughout.
+    let switches =
+        optimize_switch(&records(), &[3], (2000, 2003), &SimulationParams::default()).unwrap();
+    let entry = &switches[&3][0];
+    assert_eq!(entry.switch_after, 1, "{entry:?}");
+    assert_eq!(mean_switch_after(&switches[&3]), Some(1.0));
+}
@@ -0,0 +1,37 @@
+mod common;
+
+fn table(args: &[&str]) -> Vec<String> {
+    let output = common::run_golden(&[&["--quiet", "--lengths", "2"], args].concat());
+    common::stdout(output)
+        .lines()
+        .filter(|line| line.starts_with("20"))
+        .map(String::from)
+        .collect()
+}
+
+#[test]
+fn columns_line_up_across_start_years() {
+    let rows = table(&["--initial-capital", "1000"]);
+    assert!(rows.len() > 1);
+    for label in ["CAGR", "max drawdown", "KF tax"] {
+        let positions: Vec<Option<usize>> = rows.iter().map(|row| row.find(label)).collect();
+        assert!(
+            positions.windows(2).all(|w| w[0] == w[1]),
+            "{label}: {rows:#?}"
+        );
+    }
+    assert!(
+        rows.windows(2).all(|w| w[0].len() == w[1].len()),
+        "{rows:#?}"
+    );
+}
+
+#[test]
+fn table_width_pads_narrow_columns() {
+    let rows = table(&["--table-width", "12"]);
+    let first_value = &rows[0]["2015:".len()..];
+    assert!(
+        first_value.starts_with(&" ".repeat(5 + 8)),
+        "{first_value:?}"
+    );
+}
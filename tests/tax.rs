@@ -0,0 +1,112 @@
+use kf_vs_ak::{calculate_avkastningsskatt, parse_tax_schedule, TaxParams};
+
+const EPSILON: f64 = 1e-9;
+
+// SLR on November 30 of the previous year and the resulting rates published
+// by Skatteverket.
+
+#[test]
+fn year_2016_uses_old_supplement() {
+    let tax = calculate_avkastningsskatt(2016, 0.65, &TaxParams::default());
+    assert!((tax - 0.0042).abs() < EPSILON, "{tax}");
+}
+
+#[test]
+fn year_2019_uses_new_supplement() {
+    let tax = calculate_avkastningsskatt(2019, 0.51, &TaxParams::default());
+    assert!((tax - 0.00453).abs() < EPSILON, "{tax}");
+}
+
+#[test]
+fn year_2023_uses_new_supplement() {
+    let tax = calculate_avkastningsskatt(2023, 1.94, &TaxParams::default());
+    assert!((tax - 0.00882).abs() < EPSILON, "{tax}");
+}
+
+#[test]
+fn year_2010_has_no_floor() {
+    // Before 2012 the schablonintäkt was the SLR alone, however low.
+    let tax = calculate_avkastningsskatt(2010, 0.8, &TaxParams::default());
+    assert!((tax - 0.01 * 0.8 * 0.30).abs() < EPSILON, "{tax}");
+}
+
+#[test]
+fn year_2013_is_floored_without_supplement() {
+    let tax = calculate_avkastningsskatt(2013, 0.8, &TaxParams::default());
+    assert!((tax - 0.01 * 1.25 * 0.30).abs() < EPSILON, "{tax}");
+    let above = calculate_avkastningsskatt(2013, 1.6, &TaxParams::default());
+    assert!((above - 0.01 * 1.6 * 0.30).abs() < EPSILON, "{above}");
+}
+
+#[test]
+fn low_slr_is_floored() {
+    let tax = calculate_avkastningsskatt(2021, -0.07, &TaxParams::default());
+    assert!((tax - 0.00375).abs() < EPSILON, "{tax}");
+}
+
+#[test]
+fn slr_well_above_floor() {
+    let tax = calculate_avkastningsskatt(2020, 4.0, &TaxParams::default());
+    assert!((tax - 0.01 * 5.0 * 0.30).abs() < EPSILON, "{tax}");
+}
+
+#[test]
+fn zero_slr_is_floored() {
+    let tax = calculate_avkastningsskatt(2020, 0.0, &TaxParams::default());
+    assert!((tax - 0.01 * 1.25 * 0.30).abs() < EPSILON, "{tax}");
+}
+
+#[test]
+fn slr_at_floor_boundary() {
+    // 0.25 plus the one percentage point supplement is exactly the floor.
+    let params = TaxParams::default();
+    let at = calculate_avkastningsskatt(2020, 0.25, &params);
+    assert!((at - 0.01 * 1.25 * 0.30).abs() < EPSILON, "{at}");
+    let below = calculate_avkastningsskatt(2020, 0.24, &params);
+    assert!((below - at).abs() < EPSILON, "{below}");
+    let above = calculate_avkastningsskatt(2020, 0.26, &params);
+    assert!((above - 0.01 * 1.26 * 0.30).abs() < EPSILON, "{above}");
+}
+
+#[test]
+fn schedule_overrides_the_built_in_rule() {
+    let tax = TaxParams {
+        schedule: parse_tax_schedule(
+            "from_year,to_year,supplement,floor,base_rate\n2010,2012,2.0,0,0.5\n",
+        )
+        .unwrap(),
+        ..TaxParams::default()
+    };
+    let scheduled = calculate_avkastningsskatt(2011, 1.0, &tax);
+    assert!((scheduled - 0.015).abs() < EPSILON, "{scheduled}");
+    let built_in = calculate_avkastningsskatt(2019, 1.0, &tax);
+    assert!((built_in - 0.006).abs() < EPSILON, "{built_in}");
+}
+
+#[test]
+fn bundled_schedule_matches_the_built_in_rule() {
+    let text = include_str!("../data/tax_schedule.csv");
+    let tax = TaxParams {
+        schedule: parse_tax_schedule(text).unwrap(),
+        ..TaxParams::default()
+    };
+    for (year, slr) in [
+        (2005, 2.5),
+        (2011, 0.4),
+        (2014, 0.9),
+        (2016, 0.65),
+        (2019, 0.51),
+        (2023, 1.94),
+        (2021, -0.07),
+    ] {
+        let scheduled = calculate_avkastningsskatt(year, slr, &tax);
+        let built_in = calculate_avkastningsskatt(year, slr, &TaxParams::default());
+        assert!((scheduled - built_in).abs() < EPSILON, "{year}");
+    }
+}
+
+#[test]
+fn overlapping_periods_are_rejected() {
+    let error = parse_tax_schedule("2000,2010,1,1,0.3\n2005,,1,1,0.3\n").unwrap_err();
+    assert_eq!(error, "line 2: overlaps the period from 2000");
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use kf_vs_ak::{
+    simulate, summarize, trajectory, write_series, write_trajectory, AkRealize, Amounts, Record,
+    SimulationParams,
+};
+use std::collections::BTreeMap;
+
+fn records() -> BTreeMap<i32, Record> {
+    common::records(
+        [(2000, 100.0), (2001, 120.0), (2002, 90.0), (2003, 110.0)],
+        0.01,
+        2.0,
+    )
+}
+
+#[test]
+fn trajectory_ends_at_the_simulated_values() {
+    let records = records();
+    let params = SimulationParams::default();
+    let years = trajectory(&records, 2000, 2003, &params).unwrap();
+    assert_eq!(years.len(), 3);
+    assert!((years[0].return_ratio - 1.2).abs() < 1e-12);
+
+    let series = simulate(&records, &[1, 2, 3], (2000, 2003), &params).unwrap();
+    for (i, y) in years.iter().enumerate() {
+        let entry = &series[&(i as i32 + 1)][0];
+        assert_eq!(y.aktiekonto_net, entry.aktiekonto);
+        assert_eq!(y.kapitalförsäkring, entry.kapitalförsäkring);
+        assert_eq!(y.aktiekonto_tax, 0.0);
+    }
+    // The avkastningsskatt is taken on the value at the start of each year.
+    assert!((years[0].kapitalförsäkring_tax - 0.01).abs() < 1e-12);
+    let kf_start = years[0].kapitalförsäkring;
+    assert!((years[1].kapitalförsäkring_tax - kf_start * 0.01).abs() < 1e-12);
+}
+
+#[test]
+fn trajectory_shows_yearly_realization_tax() {
+    let params = SimulationParams {
+        ak_realize: AkRealize::Annual,
+        capital_gains_rate: 0.3,
+        ..SimulationParams::default()
+    };
+    let years = trajectory(&records(), 2000, 2003, &params).unwrap();
+
+    assert!((years[0].aktiekonto_tax - 0.2 * 0.3).abs() < 1e-12);
+    assert!((years[0].aktiekonto - 1.14).abs() < 1e-12);
+    // The loss of 2002 leaves no gain to tax.
+    assert_eq!(years[1].aktiekonto_tax, 0.0);
+}
+
+#[test]
+fn trajectory_and_win_rates_follow_the_precision() {
+    let records = records();
+    let params = SimulationParams::default();
+    let years = trajectory(&records, 2000, 2003, &params).unwrap();
+    let mut out = Vec::new();
+    write_trajectory(&mut out, 2000, &years, 3).unwrap();
+    let text = String::from_utf8(out).unwrap();
+    assert!(text.contains("\n2001:    1.200    "), "{text}");
+
+    let series = simulate(&records, &[1], (2000, 2003), &params).unwrap();
+    let entries = &series[&1];
+    let mut out = Vec::new();
+    let summary = summarize(1, entries);
+    write_series(&mut out, 1, entries, &summary, 1, &Amounts::default()).unwrap();
+    let text = String::from_utf8(out).unwrap();
+    assert!(text.contains("/3 (33.3%)"), "{text}");
+}
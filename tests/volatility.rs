@@ -0,0 +1,29 @@
+mod common;
+
+use kf_vs_ak::{sample_std_dev, simulate, summarize, SimulationParams};
+
+#[test]
+fn volatility_is_the_std_dev_of_log_returns() {
+    let records = common::records([(2000, 100.0), (2001, 110.0), (2002, 99.0)], 0.01, 2.0);
+    let series = simulate(
+        &records,
+        &[1, 2],
+        (2000, 2002),
+        &SimulationParams::default(),
+    )
+    .unwrap();
+
+    let entry = &series[&2][0];
+    let expected = sample_std_dev(&[1.1f64.ln(), 0.9f64.ln()]).unwrap();
+    let ak = entry.aktiekonto_volatility.unwrap();
+    assert!((ak - expected).abs() < 1e-12, "{ak} {expected}");
+    // The KF tax takes the same share of the start value every year, which
+    // lowers both returns by the same amount but not by the same ratio.
+    let expected = sample_std_dev(&[1.09f64.ln(), 0.89f64.ln()]).unwrap();
+    let kf = entry.kapitalförsäkring_volatility.unwrap();
+    assert!((kf - expected).abs() < 1e-12, "{kf} {expected}");
+    assert_eq!(series[&1][0].aktiekonto_volatility, None);
+
+    let summary = summarize(2, &series[&2]);
+    assert_eq!(summary.aktiekonto.mean_volatility, Some(ak));
+}
@@ -0,0 +1,45 @@
+mod common;
+
+use kf_vs_ak::{simulate_withdrawals, Record, SimulationParams};
+use std::collections::BTreeMap;
+
+/// A flat index without tax, so every pot keeps its value.
+fn flat_records(from: i32, to: i32) -> BTreeMap<i32, Record> {
+    common::flat_records(from..=to, 0.0, 0.0)
+}
+
+#[test]
+fn flat_pot_lasts_one_over_rate_years() {
+    let records = flat_records(2000, 2010);
+    let withdrawals = simulate_withdrawals(
+        &records,
+        &[1],
+        (2000, 2010),
+        &SimulationParams::default(),
+        25.0,
+    )
+    .unwrap();
+    let first = &withdrawals[&1][0];
+    assert_eq!(first.start_year, 2000);
+    assert_eq!(first.years_available, 9);
+    assert_eq!(first.aktiekonto_years, Some(4));
+    assert_eq!(first.kapitalförsäkring_years, Some(4));
+    assert_eq!(first.isk_years, Some(4));
+}
+
+#[test]
+fn pot_outlasting_the_data_has_no_count() {
+    let records = flat_records(2000, 2010);
+    let withdrawals = simulate_withdrawals(
+        &records,
+        &[1],
+        (2000, 2010),
+        &SimulationParams::default(),
+        5.0,
+    )
+    .unwrap();
+    let last = withdrawals[&1].last().unwrap();
+    assert_eq!(last.start_year, 2008);
+    assert_eq!(last.years_available, 1);
+    assert_eq!(last.kapitalförsäkring_years, None);
+}
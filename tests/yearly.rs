@@ -0,0 +1,77 @@
+use chrono::NaiveDate;
+use kf_vs_ak::{
+    coverage_gaps, dedup_dates, values_by_reference, OnDuplicate, SlrReference, YearAnchor,
+};
+use std::collections::BTreeMap;
+
+fn date(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2020, 1, day).unwrap()
+}
+
+fn records() -> Vec<(NaiveDate, f64)> {
+    vec![
+        (date(3), 1.0),
+        (date(2), 2.0),
+        (date(3), 3.0),
+        (date(3), 4.0),
+    ]
+}
+
+#[test]
+fn duplicates_keep_the_first_or_last_row() {
+    assert_eq!(
+        dedup_dates(&records(), OnDuplicate::First).unwrap(),
+        [(date(2), 2.0), (date(3), 1.0)]
+    );
+    assert_eq!(
+        dedup_dates(&records(), OnDuplicate::Last).unwrap(),
+        [(date(2), 2.0), (date(3), 4.0)]
+    );
+}
+
+#[test]
+fn duplicates_are_listed_once_in_the_error() {
+    let mut records = records();
+    records.push((date(2), 5.0));
+    assert_eq!(
+        dedup_dates(&records, OnDuplicate::Error).unwrap_err(),
+        "duplicate dates 2020-01-02, 2020-01-03"
+    );
+}
+
+#[test]
+fn coverage_gaps_lists_years_only_one_series_has() {
+    let index: BTreeMap<i32, f64> = (2000..=2005).map(|y| (y, 100.0)).collect();
+    let slr: BTreeMap<i32, f64> = (2002..=2008).map(|y| (y, 1.0)).collect();
+    assert_eq!(
+        coverage_gaps(&index, &slr, (2001, 2007)),
+        (vec![2001], vec![2006, 2007])
+    );
+    assert_eq!(coverage_gaps(&index, &slr, (2002, 2005)), (vec![], vec![]));
+}
+
+#[test]
+fn slr_reference_picks_the_rate_of_each_year() {
+    let on = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+    let records = [
+        (on(2020, 6, 30), 1.0),
+        (on(2020, 11, 27), 2.0),
+        (on(2020, 12, 30), 3.0),
+        (on(2021, 11, 30), 4.0),
+        (on(2021, 12, 31), 5.0),
+    ];
+    let by = |reference| values_by_reference(&records, reference, YearAnchor::Last);
+    assert_eq!(
+        by(SlrReference::YearEnd),
+        BTreeMap::from([(2020, 3.0), (2021, 5.0)])
+    );
+    // Each November rate sets the tax of the following year.
+    assert_eq!(
+        by(SlrReference::Nov30),
+        BTreeMap::from([(2021, 2.0), (2022, 4.0)])
+    );
+    assert_eq!(
+        by(SlrReference::Average),
+        BTreeMap::from([(2020, 2.0), (2021, 4.5)])
+    );
+}